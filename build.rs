@@ -1,3 +1,5 @@
 fn main() {
+    // Only the firmware links against ESP-IDF, the simulator and the host tests build without it
+    #[cfg(feature = "esp")]
     embuild::espidf::sysenv::output();
 }
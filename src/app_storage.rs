@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use anyhow::anyhow;
+use log::{info, warn};
+use crate::phone::{Phone, PhoneData};
+use crate::platform::Storage;
+use crate::runtime::block_on;
+use crate::ui::widgets::toast::Toast;
+
+/// Parent of the app directories, relative to the storage root
+pub const APPS_DIRECTORY: &str = "apps";
+/// Bytes each app may keep on the SD card
+pub const APP_QUOTA: u64 = 1024 * 1024;
+
+/// Bytes used by each app, by `App::app_name`, measured when the SD card is mounted
+pub type AppUsage = Arc<Mutex<BTreeMap<&'static str, u64>>>;
+
+/// Directory name of an app, e.g. `wifi_settings` for "WiFi settings"
+pub fn app_id(app_name: &str) -> String {
+    app_name
+        .chars()
+        .map(|character| match character.is_ascii_alphanumeric() {
+            true => character.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+/// Data directory of an app, paths outside of it are rejected, see `PhoneData::app_storage`
+pub struct AppStorage {
+    app_name: &'static str,
+    root: PathBuf,
+    pub quota: u64,
+    usage: AppUsage,
+}
+
+impl AppStorage {
+    /// Bytes used by the app
+    pub fn usage(&self) -> u64 {
+        self.usage.lock().unwrap().get(self.app_name).copied().unwrap_or(0)
+    }
+
+    /// Deletes every file of the app
+    pub fn clear(&self) -> anyhow::Result<()> {
+        let cleared = fs::remove_dir_all(&self.root).and_then(|()| fs::create_dir_all(&self.root));
+
+        // Failing partway leaves some of the files, which still count against the quota
+        let used = match cleared {
+            Ok(()) => 0,
+            Err(_) => directory_size(&self.root).unwrap_or(0),
+        };
+        self.usage.lock().unwrap().insert(self.app_name, used);
+        cleared?;
+
+        info!("{} data cleared", self.app_name);
+
+        Ok(())
+    }
+
+    /// Moves a file saved in the storage root before the app had its own directory, unless it has a newer one
+    pub fn adopt(&self, storage: &dyn Storage, path: &Path) -> anyhow::Result<()> {
+        let legacy_path = storage.root().join(path);
+        let app_path = self.resolve(path)?;
+
+        if !legacy_path.exists() || app_path.exists() {
+            return Ok(());
+        }
+
+        let size = fs::metadata(&legacy_path)?.len();
+        let mut usage = self.usage.lock().unwrap();
+        let used = usage.get(self.app_name).copied().unwrap_or(0);
+
+        self.check_quota(used, size)?;
+        fs::rename(&legacy_path, &app_path)?;
+        usage.insert(self.app_name, used + size);
+
+        info!("{} moved to the {} directory", path.display(), self.app_name);
+
+        Ok(())
+    }
+
+    /// Absolute path of a file of the app
+    fn resolve(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        // No `..`, nor absolute paths
+        let relative = path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        let named = path.components().any(|component| matches!(component, Component::Normal(_)));
+
+        match relative && named {
+            true => Ok(self.root.join(path)),
+            false => Err(anyhow!("{} is outside of the {} directory", path.display(), self.app_name)),
+        }
+    }
+
+    fn check_quota(&self, used: u64, added: u64) -> anyhow::Result<()> {
+        match used + added > self.quota {
+            true => Err(anyhow!("{} is out of storage, {} of {} bytes used", self.app_name, used, self.quota)),
+            false => Ok(()),
+        }
+    }
+
+    /// Runs `update` on the file if the app stays within its quota once the file holds `added` more bytes, emptied first when `truncated`
+    ///
+    /// The bytes are reserved before `update`, so that concurrent writes count each other, and released if the file does not end up
+    /// that big, e.g. when `update` failed halfway
+    fn update_file(&self, path: &Path, truncated: bool, added: u64, update: impl FnOnce(&Path) -> std::io::Result<()>) -> anyhow::Result<()> {
+        let expected = {
+            let mut usage = self.usage.lock().unwrap();
+
+            let size = file_size(path);
+            let others = usage.get(self.app_name).copied().unwrap_or(0).saturating_sub(size);
+            let expected = match truncated {
+                true => added,
+                false => size + added,
+            };
+
+            self.check_quota(others, expected)?;
+            usage.insert(self.app_name, others + expected);
+
+            expected
+        };
+
+        let updated = update(path);
+        let actual = file_size(path);
+
+        if actual != expected {
+            let mut usage = self.usage.lock().unwrap();
+            let used = usage.get(self.app_name).copied().unwrap_or(0);
+            usage.insert(self.app_name, (used + actual).saturating_sub(expected));
+        }
+
+        Ok(updated?)
+    }
+}
+
+impl Storage for AppStorage {
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn read_to_string(&self, path: &Path) -> anyhow::Result<Option<String>> {
+        let path = self.resolve(path)?;
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read_to_string(path)?))
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+        let path = self.resolve(path)?;
+        self.update_file(&path, true, content.len() as u64, |path| fs::write(path, content))
+    }
+
+    fn append(&self, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+        let path = self.resolve(path)?;
+
+        self.update_file(&path, false, content.len() as u64, |path| {
+            OpenOptions::new().create(true).append(true).open(path)?.write_all(content)
+        })
+    }
+
+    fn remove(&self, path: &Path) -> anyhow::Result<()> {
+        let path = self.resolve(path)?;
+
+        if path.exists() {
+            let mut usage = self.usage.lock().unwrap();
+            let size = file_size(&path);
+
+            fs::remove_file(path)?;
+
+            let used = usage.get(self.app_name).copied().unwrap_or(0);
+            usage.insert(self.app_name, used.saturating_sub(size));
+        }
+
+        Ok(())
+    }
+
+    fn create_dir(&self, path: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(self.resolve(path)?)?;
+        Ok(())
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+fn directory_size(path: &Path) -> anyhow::Result<u64> {
+    let mut size = 0;
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        size += match metadata.is_dir() {
+            true => directory_size(&entry.path())?,
+            false => metadata.len(),
+        };
+    }
+
+    Ok(size)
+}
+
+impl PhoneData {
+    /// Files of the app on the SD card, `None` without a card
+    pub fn app_storage(&self, app_name: &'static str) -> Option<AppStorage> {
+        let storage = self.storage.as_deref()?;
+
+        Some(AppStorage {
+            app_name,
+            root: storage.root().join(APPS_DIRECTORY).join(app_id(app_name)),
+            quota: APP_QUOTA,
+            usage: self.app_usage.clone(),
+        })
+    }
+}
+
+impl Phone {
+    /// Creates the missing app directories and measures what each app uses
+    pub(crate) fn create_app_directories(&mut self) -> anyhow::Result<()> {
+        let mut usage = BTreeMap::new();
+
+        for app in &self.apps {
+            let Some(app_storage) = self.phone_data.app_storage(app.app_name()) else {
+                return Ok(());
+            };
+
+            fs::create_dir_all(app_storage.root())?;
+            usage.insert(app.app_name(), directory_size(app_storage.root())?);
+        }
+
+        *self.phone_data.app_usage.lock().unwrap() = usage;
+
+        Ok(())
+    }
+
+    /// Deletes the files of the app, which starts over with its defaults
+    pub fn clear_app_data(&mut self, app_name: &str) -> anyhow::Result<()> {
+        let Some(index) = self.apps.iter().position(|app| app.app_name() == app_name) else {
+            return Err(anyhow!("No app named {}", app_name));
+        };
+
+        let app_name = self.apps[index].app_name();
+
+        let Some(app_storage) = self.phone_data.app_storage(app_name) else {
+            return Err(anyhow!("No SD card"));
+        };
+
+        // E.g. file watchers of the deleted files, `on_data_cleared` may start them again
+        self.phone_data.services.stop_app(app_name);
+        app_storage.clear()?;
+
+        if !self.is_disabled(index) {
+            if let Err(error) = block_on(self.apps[index].on_data_cleared(&mut self.phone_data)) {
+                warn!("{} could not reset its data: {}", app_name, error);
+            }
+        }
+
+        self.phone_data.show_toast(Toast::info(&format!("{} data cleared", app_name)));
+
+        Ok(())
+    }
+}
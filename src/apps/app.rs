@@ -4,41 +4,107 @@ use mousefood::prelude::{Frame, Rect};
 use std::any::Any;
 use async_trait::async_trait;
 use log::info;
+use crate::settings::SettingChange;
 use crate::state::PhoneState;
 
 #[derive(Debug)]
 pub struct ClickableArea(pub Rect, pub Box<dyn AppEvent>);
 
-#[async_trait]
+/// `init` and `handle_event` run on the UI thread, long work goes to `PhoneData::runtime`
+#[async_trait(?Send)]
 pub trait App: Any + Send + Sync {
     fn new_boxed() -> Box<dyn App> where Self: Sized;
     fn app_name(&self) -> &'static str;
-    fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    fn is_core(&self) -> bool;
+    async fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
     fn render(&mut self, phone_data: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType>;
 
-    fn handle_event(&mut self, phone_data: &mut PhoneData, event: &Box<dyn AppEvent>) -> anyhow::Result<Option<PhoneState>>;
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &dyn AppEvent) -> anyhow::Result<Option<PhoneState>>;
+
+    async fn on_enter(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn on_exit(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn on_pause(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn suspend(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn on_storage_mounted(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn on_setting_changed(&mut self, phone_data: &mut PhoneData, change: &SettingChange) -> anyhow::Result<()>;
+    async fn on_data_cleared(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
 }
 
 pub struct AppImpl<T: AppHandler> {
     inner: T,
 }
 
+impl<T: AppHandler> AppImpl<T> {
+    /// The services and settings the app reaches are its own while it is called
+    fn bind(&self, phone_data: &mut PhoneData) {
+        phone_data.services.bind(self.inner.app_name());
+        phone_data.settings.bind(self.inner.app_name(), self.inner.is_core());
+    }
+
+    /// So that the phone itself is never taken for an app
+    fn unbind(phone_data: &mut PhoneData) {
+        phone_data.services.unbind();
+        phone_data.settings.unbind();
+    }
+}
+
 unsafe impl<T: AppHandler + Send + 'static> Send for AppImpl<T> {}
 unsafe impl<T: AppHandler + Send + 'static> Sync for AppImpl<T> {}
 
 // This is a new trait that handles specific event types
-#[async_trait]
+#[async_trait(?Send)]
 pub trait AppHandler: Any {
     type Event: AppEvent;
 
     fn new() -> Self where Self: Sized;
     fn app_name(&self) -> &'static str;
-    fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
+    async fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()>;
     fn render(&mut self, phone_data: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType>;
-    fn handle_event(&mut self, phone_data: &mut PhoneData, event: &Self::Event) -> anyhow::Result<Option<PhoneState>>;
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &Self::Event) -> anyhow::Result<Option<PhoneState>>;
+
+    /// Needed to repair the phone, the only apps started in safe mode
+    fn is_core(&self) -> bool {
+        false
+    }
+
+    /// The app comes to the foreground, either launched or back from `on_pause`
+    async fn on_enter(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// The user left the app, e.g. back to the homepage
+    async fn on_exit(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Another app covers this one, which is entered again or exited later
+    async fn on_pause(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Memory runs low while the app is in the background, caches and buffers should be freed.
+    /// Its services are stopped already, `on_enter` may start them again
+    async fn suspend(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// An SD card was inserted after boot, files should be loaded again
+    async fn on_storage_mounted(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// A setting of the app changed, e.g. restored from a backup. Core apps hear about the settings of every app
+    async fn on_setting_changed(&mut self, _phone_data: &mut PhoneData, _change: &SettingChange) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// The user cleared the files of the app, what was loaded from them should be dropped, and its stopped services started again
+    async fn on_data_cleared(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
 
-#[async_trait]
+#[async_trait(?Send)]
 impl<T: AppHandler + Send + 'static> App for AppImpl<T> {
     fn new_boxed() -> Box<dyn App> where Self: Sized {
         Box::new(AppImpl {
@@ -50,21 +116,83 @@ impl<T: AppHandler + Send + 'static> App for AppImpl<T> {
         self.inner.app_name()
     }
 
-    fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
-        self.inner.init(phone_data)
+    fn is_core(&self) -> bool {
+        self.inner.is_core()
+    }
+
+    async fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.init(phone_data).await;
+        Self::unbind(phone_data);
+        result
     }
 
     fn render(&mut self, app_accessible: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
-        self.inner.render(app_accessible, frame, area)
+        self.bind(app_accessible);
+        let result = self.inner.render(app_accessible, frame, area);
+        Self::unbind(app_accessible);
+        result
     }
 
-    fn handle_event(&mut self, app_accessible: &mut PhoneData, event: &Box<dyn AppEvent>) -> anyhow::Result<Option<PhoneState>> {
-        if let Some(concrete_event) = event.as_ref().as_any().downcast_ref::<T::Event>() {
+    async fn handle_event(&mut self, app_accessible: &mut PhoneData, event: &dyn AppEvent) -> anyhow::Result<Option<PhoneState>> {
+        if let Some(concrete_event) = event.as_any().downcast_ref::<T::Event>() {
             info!("{:?}", concrete_event);
-            self.inner.handle_event(app_accessible, concrete_event)
+            self.bind(app_accessible);
+            let result = self.inner.handle_event(app_accessible, concrete_event).await;
+            Self::unbind(app_accessible);
+            result
         }
         else {
             Ok(None)
         }
     }
+
+    async fn on_enter(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_enter(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn on_exit(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_exit(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn on_pause(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_pause(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn suspend(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.suspend(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn on_storage_mounted(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_storage_mounted(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn on_setting_changed(&mut self, phone_data: &mut PhoneData, change: &SettingChange) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_setting_changed(phone_data, change).await;
+        Self::unbind(phone_data);
+        result
+    }
+
+    async fn on_data_cleared(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.bind(phone_data);
+        let result = self.inner.on_data_cleared(phone_data).await;
+        Self::unbind(phone_data);
+        result
+    }
 }
\ No newline at end of file
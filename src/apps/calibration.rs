@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use crate::apps::app::{AppHandler, ClickableArea};
+use crate::drivers::display::Screen;
+use crate::drivers::touch::TouchPoint;
+use crate::events::{CoreEvent, EventType};
+use crate::events::calibration::TouchCalibration;
+use crate::phone::PhoneData;
+use crate::state::PhoneState;
+use crate::ui::widgets::clickable_button::BorderedButton;
+use log::{info, warn};
+use mousefood::prelude::{Frame, Line, Position, Rect, Span, Stylize};
+use mousefood::ratatui::widgets::{Paragraph, Wrap};
+
+const TARGET_COUNT: usize = 3;
+
+pub struct CalibrationApp {
+    pub state: CalibrationAppState,
+    /// Raw touches matching the targets tapped so far
+    samples: Vec<TouchPoint>,
+    error: Option<String>,
+}
+
+pub enum CalibrationAppState {
+    Intro,
+    Target(usize),
+    Done,
+}
+
+#[derive(Debug)]
+pub enum CalibrationEvent {
+    Start,
+    Sample(usize),
+}
+
+#[async_trait(?Send)]
+impl AppHandler for CalibrationApp {
+    type Event = CalibrationEvent;
+
+    fn new() -> Self where Self: Sized {
+        CalibrationApp {
+            state: CalibrationAppState::Intro,
+            samples: vec![],
+            error: None,
+        }
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Touch calibration"
+    }
+
+    fn is_core(&self) -> bool {
+        true
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, phone_data: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
+        let go_back_rect = Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: 1,
+        };
+
+        let inner_area = Rect {
+            x: area.x + 1,
+            y: area.y,
+            width: area.width - 2,
+            height: area.height,
+        };
+
+        let title_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 2,
+            width: inner_area.width,
+            height: 1,
+        };
+
+        let text_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 4,
+            width: inner_area.width,
+            height: 3,
+        };
+
+        let button_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 4 + 3,
+            width: inner_area.width,
+            height: 3,
+        };
+
+        let events = match self.state {
+            CalibrationAppState::Intro => {
+                let go_back = Line::raw("← Go back").left_aligned().dark_gray();
+                frame.render_widget(go_back, go_back_rect);
+
+                let title = Line::raw("Touch calibration").bold().centered();
+                frame.render_widget(title, title_rect);
+
+                let text = match &self.error {
+                    None => Paragraph::new("Tap the center of each crosshair as precisely as possible"),
+                    Some(error) => Paragraph::new(error.as_str()).red(),
+                };
+                frame.render_widget(text.centered().wrap(Wrap { trim: true }), text_rect);
+
+                frame.render_widget(BorderedButton("Start"), button_rect);
+
+                vec![
+                    ClickableArea(go_back_rect, Box::new(CoreEvent::Back)),
+                    ClickableArea(button_rect, Box::new(CalibrationEvent::Start)),
+                ]
+            },
+            CalibrationAppState::Target(index) => {
+                let targets = targets(phone_data.screen);
+
+                // Under the middle target, as the first one sits at the height of the usual title row
+                let title_rect = Rect {
+                    y: targets[1].y + 2,
+                    ..title_rect
+                };
+
+                let title = Line::raw(format!("Tap the crosshair {}/{}", index + 1, TARGET_COUNT)).centered();
+                frame.render_widget(title, title_rect);
+
+                render_crosshair(frame, targets[index]);
+
+                // The current calibration may be wrong, so the whole screen counts
+                vec![
+                    ClickableArea(frame.area(), Box::new(CalibrationEvent::Sample(index)))
+                ]
+            },
+            CalibrationAppState::Done => {
+                let title = Line::raw("Touch calibration").bold().centered();
+                frame.render_widget(title, title_rect);
+
+                let text = Paragraph::new("Calibration saved").centered().green();
+                frame.render_widget(text, text_rect);
+
+                frame.render_widget(BorderedButton("Done"), button_rect);
+
+                vec![
+                    ClickableArea(button_rect, Box::new(CoreEvent::GoBackToHomepage)),
+                ]
+            }
+        };
+
+        Ok(EventType::List(events))
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &CalibrationEvent) -> anyhow::Result<Option<PhoneState>> {
+        match event {
+            CalibrationEvent::Start => {
+                self.samples.clear();
+                self.error = None;
+                self.state = CalibrationAppState::Target(0);
+            },
+            CalibrationEvent::Sample(index) => {
+                let Some(touch) = phone_data.touch.lock().unwrap().last_press else {
+                    return Ok(None);
+                };
+
+                self.samples.truncate(*index);
+                self.samples.push(touch);
+
+                if index + 1 < TARGET_COUNT {
+                    self.state = CalibrationAppState::Target(index + 1);
+                    return Ok(None);
+                }
+
+                match self.calibrate(phone_data.screen) {
+                    Ok(calibration) => {
+                        phone_data.touch.lock().unwrap().calibration = calibration;
+
+                        match phone_data.storage.as_deref() {
+                            Some(storage) => calibration.save(storage)?,
+                            None => info!("No storage, touch calibration not saved"),
+                        }
+
+                        self.state = CalibrationAppState::Done;
+                    },
+                    Err(error) => {
+                        warn!("Touch calibration failed: {}", error);
+
+                        self.error = Some(format!("{}, please try again", error));
+                        self.state = CalibrationAppState::Intro;
+                    }
+                }
+            },
+        }
+
+        Ok(None)
+    }
+
+    async fn on_exit(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        // Starts over on the next launch, rather than on the last screen
+        self.state = CalibrationAppState::Intro;
+        self.samples.clear();
+        self.error = None;
+        Ok(())
+    }
+}
+
+impl CalibrationApp {
+    fn calibrate(&self, screen: Screen) -> anyhow::Result<TouchCalibration> {
+        let raw = [0, 1, 2].map(|index| (self.samples[index].x as f32, self.samples[index].y as f32));
+        let panel = targets(screen).map(|target| target_pixel(target, screen));
+
+        TouchCalibration::from_samples(&raw, &panel)
+    }
+}
+
+/// Screen cells to tap, spread so that they are not aligned
+pub fn targets(screen: Screen) -> [Position; TARGET_COUNT] {
+    let (width, height) = screen.size();
+
+    [
+        Position::new(width / 10, height / 6),
+        Position::new(width * 9 / 10, height / 2),
+        Position::new(width / 2, height * 5 / 6),
+    ]
+}
+
+/// Center of a cell, in pixels of the portrait panel
+pub fn target_pixel(target: Position, screen: Screen) -> (f32, f32) {
+    let (width, height) = screen.size();
+    let (pixel_width, pixel_height) = screen.pixel_size();
+
+    let x = (target.x as f32 + 0.5) * pixel_width as f32 / width as f32;
+    let y = (target.y as f32 + 0.5) * pixel_height as f32 / height as f32;
+
+    screen.unrotate(x, y)
+}
+
+fn render_crosshair(frame: &mut Frame, target: Position) {
+    let cells = [
+        (target.x, target.y, "┼"),
+        (target.x - 1, target.y, "─"),
+        (target.x + 1, target.y, "─"),
+        (target.x, target.y - 1, "│"),
+        (target.x, target.y + 1, "│"),
+    ];
+
+    for (x, y, symbol) in cells {
+        frame.render_widget(Span::raw(symbol).yellow(), Rect::new(x, y, 1, 1));
+    }
+}
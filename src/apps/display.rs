@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use crate::apps::app::{AppHandler, ClickableArea};
+use crate::drivers::display::ScreenOrientation;
+use crate::events::{CoreEvent, EventType};
+use crate::phone::PhoneData;
+use crate::settings::{Setting, SettingChange};
+use crate::state::PhoneState;
+use crate::ui::widgets::clickable_button::BorderedButton;
+use mousefood::prelude::{Frame, Line, Rect, Stylize};
+
+/// Applied on boot
+pub const ORIENTATION: Setting<ScreenOrientation> = Setting::new("Display settings", "orientation", ScreenOrientation::Portrait);
+
+pub struct DisplayApp;
+
+#[derive(Debug)]
+pub enum DisplayEvent {
+    SetOrientation(ScreenOrientation),
+}
+
+#[async_trait(?Send)]
+impl AppHandler for DisplayApp {
+    type Event = DisplayEvent;
+
+    fn new() -> Self where Self: Sized {
+        DisplayApp
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Display settings"
+    }
+
+    fn is_core(&self) -> bool {
+        true
+    }
+
+    async fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        phone_data.screen.orientation = phone_data.settings.get(&ORIENTATION);
+        Ok(())
+    }
+
+    fn render(&mut self, phone_data: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
+        let go_back_rect = Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: 1,
+        };
+
+        let inner_area = Rect {
+            x: area.x + 1,
+            y: area.y,
+            width: area.width - 2,
+            height: area.height,
+        };
+
+        let go_back = Line::raw("← Go back").left_aligned().dark_gray();
+        frame.render_widget(go_back, go_back_rect);
+
+        let title = Line::raw("Orientation").bold().centered();
+        let title_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 2,
+            width: inner_area.width,
+            height: 1,
+        };
+        frame.render_widget(title, title_rect);
+
+        let mut events = vec![
+            ClickableArea(go_back_rect, Box::new(CoreEvent::Back))
+        ];
+
+        let orientations = [
+            ("Portrait", ScreenOrientation::Portrait),
+            ("Landscape", ScreenOrientation::Landscape),
+        ];
+
+        for (index, (name, orientation)) in orientations.into_iter().enumerate() {
+            let label = match phone_data.screen.orientation == orientation {
+                true => format!("● {name}"),
+                false => format!("○ {name}"),
+            };
+
+            let button_rect = Rect {
+                x: inner_area.x,
+                y: inner_area.y + 4 + (index as u16 * 3),
+                width: inner_area.width,
+                height: 3,
+            };
+            frame.render_widget(BorderedButton(&label), button_rect);
+
+            events.push(ClickableArea(button_rect, Box::new(DisplayEvent::SetOrientation(orientation))));
+        }
+
+        Ok(EventType::List(events))
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &DisplayEvent) -> anyhow::Result<Option<PhoneState>> {
+        match event {
+            DisplayEvent::SetOrientation(orientation) => {
+                phone_data.screen.orientation = *orientation;
+                phone_data.settings.set(&ORIENTATION, *orientation)?;
+            },
+        }
+
+        Ok(None)
+    }
+
+    async fn on_setting_changed(&mut self, phone_data: &mut PhoneData, change: &SettingChange) -> anyhow::Result<()> {
+        if change.is(&ORIENTATION) {
+            phone_data.screen.orientation = phone_data.settings.get(&ORIENTATION);
+        }
+
+        Ok(())
+    }
+}
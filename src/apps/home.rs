@@ -1,12 +1,11 @@
 use crate::events::{CoreEvent, EventType};
 use crate::phone::Phone;
-use crate::ui::widgets::clickable_button::BorderedButton;
 use mousefood::prelude::{Color, Frame, Rect, Stylize};
 use mousefood::ratatui::widgets::{Block, Paragraph};
-use crate::apps::app::{ClickableArea};
+use crate::events::AppEvent;
 
-impl Phone<'_> {
-    pub fn render_app_list(&self, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
+impl Phone {
+    pub fn render_app_list(&mut self, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
         let inner_area = Rect {
             x: area.x + 1,
             y: area.y,
@@ -14,22 +13,23 @@ impl Phone<'_> {
             height: area.height,
         };
 
-        let mut events = Vec::new();
+        let labels: Vec<String> = self.apps
+            .iter()
+            .map(|app| match self.disabled_apps.contains_key(app.app_name()) {
+                true => format!("{} (disabled)", app.app_name()),
+                false => String::from(app.app_name()),
+            })
+            .collect();
 
-        for (index, app) in self.apps.iter().enumerate() {
-            let app_rect = Rect {
-                x: inner_area.x,
-                y: inner_area.y + (index as u16 * 3),
-                width: inner_area.width,
-                height: 3,
-            };
+        // Disabled apps still open, to tell why they are disabled
+        let items = labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| (label.as_str(), Box::new(CoreEvent::LaunchApp(index)) as Box<dyn AppEvent>))
+            .collect();
 
-            let bordered_button = BorderedButton(app.app_name());
-            frame.render_widget(bordered_button, app_rect);
+        let events = self.app_list.render(frame, inner_area, items, |gesture| Box::new(CoreEvent::ScrollAppList(gesture)));
 
-            events.push(ClickableArea(app_rect, Box::new(CoreEvent::LaunchApp(index))));
-        }
-        
         Ok(EventType::List(events))
     }
 
@@ -1,3 +1,6 @@
 pub mod wifi;
+pub mod calibration;
+pub mod display;
+pub mod storage;
 pub mod app;
-pub mod home;
\ No newline at end of file
+pub mod home;
@@ -0,0 +1,180 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use crate::apps::app::{AppHandler, ClickableArea};
+use crate::events::{AppEvent, CoreEvent, EventType};
+use crate::events::gestures::Gesture;
+use crate::phone::PhoneData;
+use crate::state::PhoneState;
+use crate::ui::widgets::clickable_button::BorderedButton;
+use crate::ui::widgets::dialog::Dialog;
+use crate::ui::widgets::scrollable_list::ScrollableList;
+use crate::ui::widgets::toast::Toast;
+use mousefood::prelude::{Frame, Line, Rect, Stylize};
+
+/// What each app keeps on the SD card, see `AppStorage`, and the settings backup, see `Settings::export`
+pub struct StorageApp {
+    pub app_list: ScrollableList,
+}
+
+#[derive(Debug)]
+pub enum StorageEvent {
+    /// Confirmed with `CoreEvent::ClearAppData`
+    AskClear(&'static str),
+    ExportSettings,
+    /// Confirmed with `StorageEvent::ImportSettings`
+    AskImport,
+    ImportSettings,
+    ScrollApps(Gesture),
+}
+
+#[async_trait(?Send)]
+impl AppHandler for StorageApp {
+    type Event = StorageEvent;
+
+    fn new() -> Self where Self: Sized {
+        StorageApp {
+            app_list: ScrollableList::default(),
+        }
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Storage settings"
+    }
+
+    /// Clearing its data may repair an app failing to start
+    fn is_core(&self) -> bool {
+        true
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, phone_data: &mut PhoneData, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
+        let go_back_rect = Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: 1,
+        };
+
+        let inner_area = Rect {
+            x: area.x + 1,
+            y: area.y,
+            width: area.width - 2,
+            height: area.height,
+        };
+
+        let go_back = Line::raw("← Go back").left_aligned().dark_gray();
+        frame.render_widget(go_back, go_back_rect);
+
+        let title = Line::raw("App data").bold().centered();
+        let title_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 2,
+            width: inner_area.width,
+            height: 1,
+        };
+        frame.render_widget(title, title_rect);
+
+        let mut events = vec![
+            ClickableArea(go_back_rect, Box::new(CoreEvent::Back))
+        ];
+
+        if phone_data.storage.is_none() {
+            let no_card = Line::raw("No SD card").centered().dark_gray();
+            let no_card_rect = Rect {
+                x: inner_area.x,
+                y: inner_area.y + 4,
+                width: inner_area.width,
+                height: 1,
+            };
+            frame.render_widget(no_card, no_card_rect);
+
+            return Ok(EventType::List(events));
+        }
+
+        let export_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + inner_area.height - 3,
+            width: inner_area.width / 2,
+            height: 3,
+        };
+        frame.render_widget(BorderedButton("Export settings"), export_rect);
+        events.push(ClickableArea(export_rect, Box::new(StorageEvent::ExportSettings)));
+
+        let import_rect = Rect {
+            x: export_rect.x + export_rect.width,
+            y: export_rect.y,
+            width: inner_area.width - export_rect.width,
+            height: 3,
+        };
+        frame.render_widget(BorderedButton("Import settings"), import_rect);
+        events.push(ClickableArea(import_rect, Box::new(StorageEvent::AskImport)));
+
+        let apps_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 4,
+            width: inner_area.width,
+            height: inner_area.height.saturating_sub(4 + export_rect.height),
+        };
+
+        let labels: Vec<(&'static str, String)> = phone_data.app_usage
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(app_name, bytes)| (*app_name, format!("{}: {}", app_name, format_size(*bytes))))
+            .collect();
+
+        let apps = labels
+            .iter()
+            .map(|(app_name, label)| (label.as_str(), Box::new(StorageEvent::AskClear(app_name)) as Box<dyn AppEvent>))
+            .collect();
+
+        events.extend(self.app_list.render(frame, apps_rect, apps, |gesture| Box::new(StorageEvent::ScrollApps(gesture))));
+
+        Ok(EventType::List(events))
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &StorageEvent) -> anyhow::Result<Option<PhoneState>> {
+        match event {
+            StorageEvent::AskClear(app_name) => {
+                let title = format!("Clear {} data?", app_name);
+                phone_data.show_dialog(Dialog::confirm(&title, "Its files on the SD card are deleted", CoreEvent::ClearAppData(app_name)));
+            },
+            StorageEvent::ExportSettings => {
+                let Some(storage) = phone_data.storage.as_deref() else {
+                    return Err(anyhow!("No SD card"));
+                };
+
+                phone_data.settings.export(storage)?;
+                phone_data.show_toast(Toast::info("Settings exported"));
+            },
+            StorageEvent::AskImport => {
+                phone_data.show_dialog(Dialog::confirm("Import settings?", "Every setting is replaced by the SD card backup", StorageEvent::ImportSettings));
+            },
+            StorageEvent::ImportSettings => {
+                let Some(storage) = phone_data.storage.as_deref() else {
+                    return Err(anyhow!("No SD card"));
+                };
+
+                let toast = match phone_data.settings.import(storage)? {
+                    true => Toast::info("Settings imported"),
+                    false => Toast::error("No settings backup"),
+                };
+                phone_data.show_toast(toast);
+            },
+            StorageEvent::ScrollApps(gesture) => self.app_list.handle_gesture(gesture),
+        }
+
+        Ok(None)
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
@@ -1,40 +1,67 @@
-use std::fs::{OpenOptions};
-use std::io::{Read, Write};
 use std::path::{PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
+use anyhow::anyhow;
+use async_trait::async_trait;
 use crate::apps::app::{AppHandler, ClickableArea};
-use crate::events::{CoreEvent, EventType};
-use crate::phone::PhoneData;
+use crate::events::{AppEvent, CoreEvent, EventType};
+use crate::events::gestures::Gesture;
+use crate::phone::{Detection, PhoneData};
+use crate::platform::{SharedWifi, Storage};
+use crate::runtime::unblock;
 use crate::state::PhoneState;
 use crate::ui::widgets::clickable_button::BorderedButton;
 use crate::ui::widgets::keyboard::KeyboardLayout;
-use embedded_svc::wifi::AuthMethod;
-use esp_idf_svc::wifi::{AccessPointInfo, ClientConfiguration, Configuration};
-use log::info;
+use crate::ui::widgets::scrollable_list::ScrollableList;
+use embedded_svc::wifi::{AccessPointInfo, AuthMethod, ClientConfiguration, Configuration};
+use log::{info, warn};
 use mousefood::prelude::{Frame, Line, Rect, Stylize};
 use mousefood::ratatui::widgets::{Block, Paragraph};
 
+/// Entering the app again within it keeps the scanned access points
+const SCAN_FRESHNESS: Duration = Duration::from_secs(30);
+
 pub struct WifiApp {
     pub state: WifiAppState,
     pub access_points: Vec<AccessPointInfo>,
     pub known_wifis: Vec<ClientConfiguration>,
     pub known_wifis_path: PathBuf,
+    pub network_list: ScrollableList,
+    /// Only the first scan connects to a known network, the user chooses afterwards
+    pub auto_connect: bool,
+    /// Password screen to open once the access point is scanned, see `WifiEvent::TypePasswordFor`, dropped when the app is left
+    pending_ssid: Option<String>,
+    /// Between `on_enter` and `on_exit` or `on_pause`, as `WifiEvent::Scanned` reaches the app in the background too
+    foreground: bool,
+    /// Password typed when the app got paused, given back to the keyboard by `on_enter`
+    password_draft: Option<String>,
+    /// A scan is running, its `WifiEvent::Scanned` is on its way
+    scanning: bool,
+    scanned_at: Option<Instant>,
 }
 
 pub enum WifiAppState {
     Scanning,
     DisplayingNetworks,
-    TypingPassword(usize)
+    /// Kept while scans replace `WifiApp::access_points`
+    TypingPassword(AccessPointInfo),
 }
 
 #[derive(Debug)]
 pub enum WifiEvent {
     Scan,
+    Scanned(Vec<AccessPointInfo>),
     DisplayNetworks,
     TypePassword(usize),
-    Connect(usize)
+    /// Deep link to the password screen, e.g. `DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(ssid))`
+    TypePasswordFor(String),
+    Connect,
+    /// Posted by the connection running in the background, see `WifiApp::connect`
+    ConnectFailed(String),
+    ScrollNetworks(Gesture),
 }
 
+#[async_trait(?Send)]
 impl AppHandler for WifiApp {
     type Event = WifiEvent;
 
@@ -55,7 +82,14 @@ impl AppHandler for WifiApp {
                     ..Default::default()
                 }
             ],
-            known_wifis_path: PathBuf::from("/sdcard/wifis.dat"),
+            known_wifis_path: PathBuf::from("wifis.dat"),
+            network_list: ScrollableList::default(),
+            auto_connect: true,
+            pending_ssid: None,
+            foreground: false,
+            password_draft: None,
+            scanning: false,
+            scanned_at: None,
         }
     }
     
@@ -63,34 +97,9 @@ impl AppHandler for WifiApp {
         "WiFi settings"
     }
 
-    fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
-        if self.known_wifis_path.exists() {
-            let mut file = OpenOptions::new()
-                .read(true)
-                .open(self.known_wifis_path.as_path())?;
-
-            let mut file_content = String::new();
-            file.read_to_string(&mut file_content)?;
-
-            self.known_wifis = serde_json::from_str(&file_content)?;
-        }
-        else {
-            info!("No known wifi found");
-            self.save_known_wifis()?;
-        }
-
-        let wifi = phone_data.wifi.as_mut().unwrap();
-        let aps = wifi.scan()?;
-        self.access_points = aps;
-
-        for ap in &self.access_points {
-            for known_wifi in &self.known_wifis {
-                if ap.ssid == known_wifi.ssid {
-                    wifi.set_configuration(&Configuration::Client(known_wifi.clone()))?;
-                    wifi.connect()?;
-                }
-            }
-        }
+    async fn init(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.load_known_wifis(phone_data)?;
+        self.start_scan(phone_data);
 
         Ok(())
     }
@@ -110,9 +119,16 @@ impl AppHandler for WifiApp {
             height: area.height,
         };
 
-        let events = match self.state {
+        let events = match &self.state {
             WifiAppState::Scanning => {
-                let scanning = Line::raw("Scanning...").centered().dark_gray();
+                let go_back = Line::raw("← Go back").left_aligned().dark_gray();
+                frame.render_widget(go_back, go_back_rect);
+
+                let text = match phone_data.wifi_hardware {
+                    Detection::Missing => "No WiFi hardware",
+                    _ => "Scanning...",
+                };
+                let scanning = Line::raw(text).centered().dark_gray();
                 let scanning_rect = Rect {
                     x: inner_area.x,
                     y: inner_area.y + 2,
@@ -121,7 +137,9 @@ impl AppHandler for WifiApp {
                 };
                 frame.render_widget(scanning, scanning_rect);
 
-                return Ok(EventType::Auto(Box::new(WifiEvent::Scan)));
+                vec![
+                    ClickableArea(go_back_rect, Box::new(CoreEvent::Back))
+                ]
             },
             WifiAppState::DisplayingNetworks => {
                 let go_back = Line::raw("← Go back").left_aligned().dark_gray();
@@ -138,30 +156,32 @@ impl AppHandler for WifiApp {
                 };
                 frame.render_widget(aps, aps_rect);
 
+                let networks_rect = Rect {
+                    x: inner_area.x,
+                    y: inner_area.y + 4,
+                    width: inner_area.width,
+                    height: inner_area.height.saturating_sub(4),
+                };
+
+                let networks = self.access_points
+                    .iter()
+                    .enumerate()
+                    .map(|(index, ap)| (ap.ssid.as_str(), Box::new(WifiEvent::TypePassword(index)) as Box<dyn AppEvent>))
+                    .collect();
+
                 let mut events = vec![
-                    ClickableArea(go_back_rect, Box::new(CoreEvent::GoBackToHomepage))
+                    ClickableArea(go_back_rect, Box::new(CoreEvent::Back))
                 ];
 
-                for (index, ap) in self.access_points.iter().enumerate() {
-                    let ap_span = BorderedButton(ap.ssid.as_str());
-                    let rect = Rect {
-                        x: inner_area.x,
-                        y: inner_area.y + 4 + (3 * index as u16),
-                        width: inner_area.width,
-                        height: 3,
-                    };
-                    frame.render_widget(ap_span, rect);
-
-                    events.push(ClickableArea(rect, Box::new(WifiEvent::TypePassword(index))));
-                }
+                events.extend(self.network_list.render(frame, networks_rect, networks, |gesture| Box::new(WifiEvent::ScrollNetworks(gesture))));
 
                 events
             },
-            WifiAppState::TypingPassword(index) => {
+            WifiAppState::TypingPassword(access_point) => {
                 let go_back = Line::raw("← Go back").left_aligned().dark_gray();
                 frame.render_widget(go_back, go_back_rect);
 
-                let ap_name = Line::raw(self.access_points[index].ssid.as_str())
+                let ap_name = Line::raw(access_point.ssid.as_str())
                     .bold()
                     .centered();
                 let ap_name_rect = Rect {
@@ -192,8 +212,8 @@ impl AppHandler for WifiApp {
                 frame.render_widget(connect, connect_rect);
 
                 vec![
-                    ClickableArea(go_back_rect, Box::new(WifiEvent::DisplayNetworks)),
-                    ClickableArea(connect_rect, Box::new(WifiEvent::Connect(index))),
+                    ClickableArea(go_back_rect, Box::new(CoreEvent::Back)),
+                    ClickableArea(connect_rect, Box::new(WifiEvent::Connect)),
                 ]
             }
         };
@@ -201,75 +221,277 @@ impl AppHandler for WifiApp {
         Ok(EventType::List(events))
     }
 
-    fn handle_event(&mut self, phone_data: &mut PhoneData, event: &WifiEvent) -> anyhow::Result<Option<PhoneState>> {
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &WifiEvent) -> anyhow::Result<Option<PhoneState>> {
         match event {
-            WifiEvent::Scan => {
-                let wifi = phone_data.wifi.as_mut().unwrap();
-                let aps = wifi.scan()?;
-                self.access_points = aps;
-                self.state = WifiAppState::DisplayingNetworks;
+            WifiEvent::Scan => self.start_scan(phone_data),
+            WifiEvent::Scanned(access_points) => {
+                self.access_points = access_points.clone();
+                self.network_list = ScrollableList::default();
+                self.scanning = false;
+                self.scanned_at = Some(Instant::now());
+
+                // Not while typing a password, which the user asked for in the meantime
+                if let WifiAppState::Scanning = self.state {
+                    self.state = WifiAppState::DisplayingNetworks;
+                }
+
+                if self.auto_connect {
+                    self.auto_connect = false;
+                    self.connect_known_wifi(phone_data);
+                }
+
+                if let (true, Some(ssid)) = (self.foreground, self.pending_ssid.take()) {
+                    self.type_password_for(phone_data, &ssid);
+                }
             }
             WifiEvent::DisplayNetworks => {
                 self.state = WifiAppState::DisplayingNetworks;
                 phone_data.hide_keyboard();
             },
-            WifiEvent::TypePassword(index) => {
-                self.state = WifiAppState::TypingPassword(*index);
-                phone_data.display_keyboard(KeyboardLayout::Azerty, true);
+            WifiEvent::TypePassword(index) => self.type_password(phone_data, *index),
+            WifiEvent::TypePasswordFor(ssid) => match self.state {
+                WifiAppState::Scanning => self.pending_ssid = Some(ssid.clone()),
+                _ => self.type_password_for(phone_data, ssid),
             },
-            WifiEvent::Connect(index) => {
+            WifiEvent::Connect => {
+                let WifiAppState::TypingPassword(access_point) = &self.state else {
+                    return Ok(None);
+                };
+
                 let password_text = phone_data.keyboard.as_ref().unwrap().text.clone();
                 let password: heapless::String<64> = heapless::String::try_from(password_text.as_str()).unwrap();
                 phone_data.hide_keyboard();
 
-                match &mut phone_data.wifi {
+                match phone_data.wifi.clone() {
                     None => {}
                     Some(wifi) => {
                         let client_configuration = ClientConfiguration {
-                            ssid: self.access_points[*index].ssid.clone(),
-                            bssid: Some(self.access_points[*index].bssid.clone()),
-                            auth_method: self.access_points[*index].auth_method.unwrap_or_default(),
+                            ssid: access_point.ssid.clone(),
+                            bssid: Some(access_point.bssid),
+                            auth_method: access_point.auth_method.unwrap_or_default(),
                             password,
-                            channel: Some(self.access_points[*index].channel),
+                            channel: Some(access_point.channel),
                             ..Default::default()
                         };
 
                         self.known_wifis.push(client_configuration.clone());
-                        self.save_known_wifis()?;
+                        self.save_known_wifis(phone_data)?;
 
                         info!("Connecting to {}", client_configuration.ssid);
                         dbg!(&client_configuration);
 
-                        wifi.set_configuration(&Configuration::Client(client_configuration))?;
-                        wifi.connect()?;
-
-                        self.state = WifiAppState::Scanning;
-                        self.access_points = vec![];
+                        connect(phone_data, wifi, vec![client_configuration]);
 
                         return Ok(Some(PhoneState::Homepage));
                     }
                 }
-            }
+            },
+            WifiEvent::ConnectFailed(error) => return Err(anyhow!("WiFi connection failed: {}", error)),
+            WifiEvent::ScrollNetworks(gesture) => self.network_list.handle_gesture(gesture),
         };
 
 
         Ok(None)
     }
+
+    async fn on_enter(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.foreground = true;
+
+        // Resumed on the password screen
+        if let (WifiAppState::TypingPassword(_), Some(password)) = (&self.state, self.password_draft.take()) {
+            phone_data.display_keyboard(KeyboardLayout::Azerty, true);
+
+            if let Some(keyboard) = phone_data.keyboard.as_mut() {
+                keyboard.text = password;
+            }
+        }
+
+        // The networks around may have changed since the last visit, unless it was a moment ago
+        if self.scanned_at.map_or(true, |scanned_at| scanned_at.elapsed() >= SCAN_FRESHNESS) {
+            self.start_scan(phone_data);
+        }
+
+        Ok(())
+    }
+
+    async fn on_exit(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.foreground = false;
+        self.pending_ssid = None;
+        self.password_draft = None;
+
+        if let WifiAppState::TypingPassword(_) = self.state {
+            phone_data.hide_keyboard();
+        }
+
+        self.state = match self.scanning {
+            true => WifiAppState::Scanning,
+            false => WifiAppState::DisplayingNetworks,
+        };
+
+        Ok(())
+    }
+
+    async fn on_pause(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.foreground = false;
+        self.pending_ssid = None;
+
+        // The keyboard would stay over the app taking the foreground
+        if let WifiAppState::TypingPassword(_) = self.state {
+            self.password_draft = phone_data.keyboard.as_ref().map(|keyboard| keyboard.text.clone());
+            phone_data.hide_keyboard();
+        }
+
+        Ok(())
+    }
+
+    async fn suspend(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        // Scanned again on entering
+        self.access_points = vec![];
+        self.network_list = ScrollableList::default();
+        self.scanned_at = None;
+        Ok(())
+    }
+
+    async fn on_storage_mounted(&mut self, phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.load_known_wifis(phone_data)
+    }
+
+    async fn on_data_cleared(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        self.known_wifis = WifiApp::new().known_wifis;
+        Ok(())
+    }
 }
 
 impl WifiApp {
-    pub fn save_known_wifis(&mut self) -> anyhow::Result<()> {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(self.known_wifis_path.as_path())?;
+    /// Scans on the runtime, the access points come back as `WifiEvent::Scanned`. Does nothing while a scan is running.
+    pub fn start_scan(&mut self, phone_data: &PhoneData) {
+        let Some(wifi) = phone_data.wifi.clone() else {
+            return;
+        };
+
+        if self.scanning {
+            return;
+        }
+
+        // A rescan in the background leaves the password screen alone
+        if let WifiAppState::DisplayingNetworks = self.state {
+            self.state = WifiAppState::Scanning;
+        }
+
+        let events = phone_data.events.clone();
+        self.scanning = true;
+
+        phone_data.runtime.spawn(async move {
+            let access_points = match unblock(move || wifi.lock().unwrap().scan()).await {
+                Ok(Ok(access_points)) => access_points,
+                Ok(Err(error)) | Err(error) => {
+                    warn!("WiFi scan failed: {}", error);
+                    vec![]
+                }
+            };
+
+            events.post(WifiEvent::Scanned(access_points));
+        });
+    }
+
+    /// `index` in the access points listed when it was tapped, which a scan may have replaced since
+    fn type_password(&mut self, phone_data: &mut PhoneData, index: usize) {
+        let Some(access_point) = self.access_points.get(index) else {
+            return;
+        };
+
+        self.state = WifiAppState::TypingPassword(access_point.clone());
+        phone_data.display_keyboard(KeyboardLayout::Azerty, true);
+        phone_data.push_screen(WifiEvent::DisplayNetworks);
+    }
+
+    fn type_password_for(&mut self, phone_data: &mut PhoneData, ssid: &str) {
+        match self.access_points.iter().position(|ap| ap.ssid.as_str() == ssid) {
+            Some(index) => self.type_password(phone_data, index),
+            None => warn!("{} is out of reach", ssid),
+        }
+    }
+
+    fn connect_known_wifi(&self, phone_data: &PhoneData) {
+        let Some(wifi) = phone_data.wifi.clone() else {
+            return;
+        };
+
+        let mut configurations = vec![];
+
+        for ap in &self.access_points {
+            for known_wifi in &self.known_wifis {
+                if ap.ssid == known_wifi.ssid {
+                    configurations.push(known_wifi.clone());
+                }
+            }
+        }
+
+        if !configurations.is_empty() {
+            connect(phone_data, wifi, configurations);
+        }
+    }
+
+    /// Without storage, only the default known wifis are used
+    pub fn load_known_wifis(&mut self, phone_data: &PhoneData) -> anyhow::Result<()> {
+        let (Some(storage), Some(app_storage)) = (phone_data.storage.as_deref(), phone_data.app_storage(self.app_name())) else {
+            return Ok(());
+        };
+
+        // Saved in the SD card root by older versions
+        app_storage.adopt(storage, &self.known_wifis_path)?;
+
+        match app_storage.read_to_string(&self.known_wifis_path)? {
+            Some(file_content) => {
+                self.known_wifis = serde_json::from_str(&file_content)?;
+            }
+            None => {
+                info!("No known wifi found");
+                self.save_known_wifis(phone_data)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save_known_wifis(&mut self, phone_data: &PhoneData) -> anyhow::Result<()> {
+        let Some(app_storage) = phone_data.app_storage(self.app_name()) else {
+            info!("No storage, known wifis not saved");
+            return Ok(());
+        };
 
         let known_wifis_string = serde_json::to_string(&self.known_wifis)?;
-        file.write_all(known_wifis_string.as_bytes())?;
+        app_storage.write(&self.known_wifis_path, known_wifis_string.as_bytes())?;
 
         info!("Known wifis saved");
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Connects on the runtime, as the driver is busy for the whole scan when one is running.
+/// Failures come back as `WifiEvent::ConnectFailed`, successes are seen by the next system check.
+fn connect(phone_data: &PhoneData, wifi: SharedWifi, configurations: Vec<ClientConfiguration>) {
+    let events = phone_data.events.clone();
+
+    phone_data.runtime.spawn(async move {
+        let result = unblock(move || -> anyhow::Result<()> {
+            let mut wifi = wifi.lock().unwrap();
+
+            for configuration in configurations {
+                wifi.set_configuration(&Configuration::Client(configuration))?;
+                wifi.connect()?;
+            }
+
+            Ok(())
+        }).await;
+
+        match result {
+            Ok(Ok(())) => events.wake(),
+            Ok(Err(error)) | Err(error) => {
+                warn!("WiFi connection failed: {}", error);
+                events.post(WifiEvent::ConnectFailed(error.to_string()));
+            }
+        }
+    });
+}
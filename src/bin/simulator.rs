@@ -0,0 +1,204 @@
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window};
+use esp32_phone_os::crash::{self, CrashRecord};
+use esp32_phone_os::drivers::display::Screen;
+use esp32_phone_os::drivers::framebuffer::DirtyFramebuffer;
+use esp32_phone_os::drivers::touch::TouchEvent;
+use esp32_phone_os::phone::{Detection, Phone};
+use esp32_phone_os::platform::simulator::{HostDirectory, HostSettings, SimulatedWifi, SimulatorTouch, SimulatorTouchSender};
+use esp32_phone_os::platform::recording::TouchReplay;
+use esp32_phone_os::platform::SystemTimeSource;
+use log::info;
+use mousefood::prelude::*;
+
+const DEFAULT_SD_CARD_DIRECTORY: &str = "sdcard";
+const SETTINGS_FILE: &str = "settings.nvs.json";
+const WINDOW_TITLE: &str = "Phone-OS simulator";
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    info!("Hello from the simulator!");
+
+    // First argument overrides the directory standing in for /sdcard
+    let sd_card_directory = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SD_CARD_DIRECTORY));
+
+    // Second argument is an optional touch recording to replay instead of the mouse
+    let touch_replay = match env::args().nth(2) {
+        None => None,
+        Some(path) => Some(TouchReplay::open(Path::new(&path))?),
+    };
+
+    let mut phone = Phone::new(Box::new(SystemTimeSource));
+
+    /* ===== SD Card ===== */
+
+    // Mounted first, as it tells which display to simulate
+    phone.phone_data.storage = Some(Box::new(HostDirectory::mount(&sd_card_directory)?));
+    phone.phone_data.sd_card = Detection::Detected;
+
+    // The NVS partition of the phone
+    phone.phone_data.settings.load(Box::new(HostSettings::new(Path::new(SETTINGS_FILE))))?;
+
+    // Panics are saved to the SD card directory once `init` loaded it, and shown on the next start
+    crash::install_panic_hook(&phone);
+
+    /* ===== Display & touch ===== */
+
+    phone.load_display_model()?;
+
+    let (touch_controller, touch_sender) = SimulatorTouch::new(phone.phone_data.screen.model);
+
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let window = Rc::new(RefCell::new(Window::new(WINDOW_TITLE, &output_settings)));
+    let mut screen = phone.phone_data.screen;
+
+    /* ===== TUI ===== */
+
+    // Splash screen, the backend borrows the display until the end of the block
+    {
+        let mut display = simulator_display(screen);
+        let mut framebuffer = DirtyFramebuffer::new(&mut display);
+        let window_events = Rc::new(WindowEvents::new(&window, &touch_sender, screen));
+        let mut terminal = simulator_terminal(&mut framebuffer, window_events)?;
+
+        terminal.draw(|frame| phone.render_homepage(frame))?;
+    }
+
+    /* ===== WiFi ===== */
+
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(SimulatedWifi::new())));
+    phone.phone_data.wifi_hardware = Detection::Detected;
+
+    /* ===== Main loop ===== */
+
+    match touch_replay {
+        None => phone.spawn_touch_thread(touch_controller),
+        Some(touch_replay) => phone.spawn_touch_thread(touch_replay),
+    };
+
+    if let Some(record) = crash::catch(|| phone.init()) {
+        quit_after_crash(&phone, &window, &touch_sender, &record)?;
+    }
+
+    // Each rotation rebuilds the display and its window, as their size changes
+    loop {
+        if phone.phone_data.screen != screen {
+            screen = phone.phone_data.screen;
+            *window.borrow_mut() = Window::new(WINDOW_TITLE, &output_settings);
+        }
+
+        let mut display = simulator_display(screen);
+        let mut framebuffer = DirtyFramebuffer::new(&mut display);
+        let window_events = Rc::new(WindowEvents::new(&window, &touch_sender, screen));
+
+        // Clicks reach the phone even while nothing is drawn
+        let hook_events = window_events.clone();
+        phone.main_thread_hook = Some(Box::new(move || hook_events.pump()));
+        let terminal = simulator_terminal(&mut framebuffer, window_events)?;
+
+        if let Some(record) = crash::catch(|| phone.event_loop(terminal)) {
+            quit_after_crash(&phone, &window, &touch_sender, &record)?;
+        }
+    }
+}
+
+/// Shows the crash screen, the simulator has to be started again to "reboot"
+fn quit_after_crash(phone: &Phone, window: &Rc<RefCell<Window>>, touch_sender: &SimulatorTouchSender, record: &CrashRecord) -> anyhow::Result<()> {
+    let screen = phone.phone_data.screen;
+    let mut display = simulator_display(screen);
+    let mut framebuffer = DirtyFramebuffer::new(&mut display);
+    let window_events = Rc::new(WindowEvents::new(window, touch_sender, screen));
+    let mut terminal = simulator_terminal(&mut framebuffer, window_events)?;
+
+    crash::wait_for_reboot(&mut terminal, &phone.events, record, crash::CRASH_REBOOT_TIMEOUT)?;
+
+    exit(1)
+}
+
+fn simulator_display(screen: Screen) -> SimulatorDisplay<Rgb565> {
+    let (width, height) = screen.pixel_size();
+
+    SimulatorDisplay::new(Size::new(width as u32, height as u32))
+}
+
+/// Turns the mouse events of the window into touches
+struct WindowEvents {
+    window: Rc<RefCell<Window>>,
+    touch_sender: SimulatorTouchSender,
+    screen: Screen,
+    pressed: Cell<bool>,
+}
+
+impl WindowEvents {
+    fn new(window: &Rc<RefCell<Window>>, touch_sender: &SimulatorTouchSender, screen: Screen) -> Self {
+        WindowEvents {
+            window: window.clone(),
+            touch_sender: touch_sender.clone(),
+            screen,
+            pressed: Cell::new(false),
+        }
+    }
+
+    /// Needs the window to have shown a frame
+    fn pump(&self) {
+        for event in self.window.borrow_mut().events() {
+            match event {
+                SimulatorEvent::Quit => exit(0),
+                SimulatorEvent::MouseButtonDown { mouse_btn: MouseButton::Left, point } => {
+                    self.pressed.set(true);
+                    self.send(point, TouchEvent::Press);
+                },
+                SimulatorEvent::MouseMove { point } if self.pressed.get() => {
+                    self.send(point, TouchEvent::Move);
+                },
+                SimulatorEvent::MouseButtonUp { mouse_btn: MouseButton::Left, point } => {
+                    self.pressed.set(false);
+                    self.send(point, TouchEvent::Release);
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// The touch controller sees the portrait panel
+    fn send(&self, point: Point, event: TouchEvent) {
+        let (x, y) = self.screen.unrotate(point.x as f32, point.y as f32);
+        self.touch_sender.send(x as i32, y as i32, event);
+    }
+}
+
+type SimulatorFramebuffer<'display> = DirtyFramebuffer<'display, SimulatorDisplay<Rgb565>>;
+
+/// Shows the display in the window on each flush, the events are pumped by `Phone::main_thread_hook` as well
+fn simulator_terminal<'framebuffer, 'display>(
+    framebuffer: &'framebuffer mut SimulatorFramebuffer<'display>,
+    window_events: Rc<WindowEvents>,
+) -> anyhow::Result<Terminal<EmbeddedBackend<'framebuffer, SimulatorFramebuffer<'display>, Rgb565>>> {
+    let backend_config = EmbeddedBackendConfig {
+        flush_callback: Box::new(move |framebuffer: &mut SimulatorFramebuffer| {
+            // Same path as on the phone, although the window shows the whole display anyway
+            framebuffer.flush().ok();
+            window_events.window.borrow_mut().update(framebuffer.display());
+
+            // The crash screen has no main loop to pump them
+            window_events.pump();
+        }),
+        ..Default::default()
+    };
+
+    let backend = EmbeddedBackend::new(framebuffer, backend_config);
+
+    Ok(Terminal::new(backend)?)
+}
@@ -0,0 +1,296 @@
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use log::{error, info, warn};
+use mousefood::prelude::{Backend, Frame, Line, Rect, Stylize, Terminal};
+use mousefood::ratatui::widgets::{Paragraph, Wrap};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use crate::events::gestures::Gesture;
+use crate::events::queue::PhoneEvent;
+use crate::phone::Phone;
+use crate::platform::{free_memory, Storage};
+use crate::ui::widgets::clickable_button::BorderedButton;
+use crate::ui::widgets::dialog::Dialog;
+
+/// Every crash, one JSON record per line
+pub const CRASH_LOG_PATH: &str = "crash_log.jsonl";
+/// The crash not shown to the user yet, see `Phone::report_last_crash`
+pub const LAST_CRASH_PATH: &str = "last_crash.json";
+
+const CRASH_REDRAW_PERIOD: Duration = Duration::from_millis(100);
+/// Reboots without a tap, as a thread parked by the panic hook may keep the event queue open while the touch thread is gone
+pub const CRASH_REBOOT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Counted from `install_panic_hook`, early in the boot
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+/// Set by the panic hook, taken by `catch`
+static PANIC: Mutex<Option<CrashRecord>> = Mutex::new(None);
+/// Of the phone passed to `install_panic_hook`
+static STORAGE_ROOT: OnceCell<CrashStorageRoot> = OnceCell::new();
+/// The UI thread and what shows its panics, see `install_crash_screen`
+static CRASH_SCREEN: OnceCell<(ThreadId, CrashScreen)> = OnceCell::new();
+
+/// Root of the mounted storage, `None` without an SD card, kept up to date by the phone
+pub type CrashStorageRoot = Arc<Mutex<Option<PathBuf>>>;
+type CrashScreen = Box<dyn Fn(&CrashRecord) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub message: String,
+    /// Source file, line and column of the panic, unknown for errors
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub uptime_secs: u64,
+    /// Unknown on the host
+    pub free_heap: Option<usize>,
+}
+
+impl CrashRecord {
+    pub fn new(message: &str, location: Option<String>) -> Self {
+        CrashRecord {
+            message: String::from(message),
+            location,
+            thread: std::thread::current().name().map(String::from),
+            uptime_secs: STARTED.elapsed().as_secs(),
+            free_heap: free_memory(),
+        }
+    }
+
+    /// Appended to the crash log, and kept aside for the next boot
+    pub fn save(&self, storage: &dyn Storage) -> anyhow::Result<()> {
+        let content = serde_json::to_string(self)?;
+
+        storage.append(Path::new(CRASH_LOG_PATH), format!("{}\n", content).as_bytes())?;
+        storage.write(Path::new(LAST_CRASH_PATH), content.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// The crash saved by the previous boot, once
+    pub fn take_last(storage: &dyn Storage) -> anyhow::Result<Option<Self>> {
+        let Some(content) = storage.read_to_string(Path::new(LAST_CRASH_PATH))? else {
+            return Ok(None);
+        };
+
+        storage.remove(Path::new(LAST_CRASH_PATH))?;
+
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Full screen, as nothing else can be trusted anymore. Returns the reboot button.
+    pub fn render(&self, frame: &mut Frame) -> Rect {
+        let area = frame.area();
+
+        let inner_area = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
+        };
+
+        let title_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y,
+            width: inner_area.width,
+            height: 1,
+        };
+        frame.render_widget(Line::raw("The phone crashed").red().bold().centered(), title_rect);
+
+        let reboot_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + inner_area.height.saturating_sub(3),
+            width: inner_area.width,
+            height: 3,
+        };
+
+        let mut lines = vec![
+            Line::raw(self.message.as_str()).white(),
+            Line::raw(""),
+        ];
+
+        if let Some(location) = &self.location {
+            lines.push(Line::raw(format!("at {}", location)).dark_gray());
+        }
+
+        if let Some(thread) = &self.thread {
+            lines.push(Line::raw(format!("in thread {}", thread)).dark_gray());
+        }
+
+        lines.push(Line::raw(format!("Up for {}", format_uptime(self.uptime_secs))).dark_gray());
+
+        if let Some(free_heap) = self.free_heap {
+            lines.push(Line::raw(format!("{} bytes of free heap", free_heap)).dark_gray());
+        }
+
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(format!("Saved to {}", CRASH_LOG_PATH)).dark_gray());
+
+        let details_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 2,
+            width: inner_area.width,
+            height: reboot_rect.y.saturating_sub(inner_area.y + 3),
+        };
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), details_rect);
+
+        frame.render_widget(BorderedButton("Reboot"), reboot_rect);
+
+        reboot_rect
+    }
+}
+
+impl Display for CrashRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+
+        write!(f, ", up for {}", format_uptime(self.uptime_secs))
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Saves every panic to the storage of the phone, and wakes the main loop up for panics of background threads
+pub fn install_panic_hook(phone: &Phone) {
+    Lazy::force(&STARTED);
+    STORAGE_ROOT.set(phone.crash_storage_root.clone()).ok();
+
+    let events = phone.phone_data.events.clone();
+
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+            (Some(message), _) => message.to_string(),
+            (None, Some(message)) => message.clone(),
+            (None, None) => String::from("Unknown panic"),
+        };
+        let location = info.location().map(|location| location.to_string());
+
+        let record = CrashRecord::new(&message, location);
+        save(&record);
+
+        *PANIC.lock().unwrap_or_else(PoisonError::into_inner) = Some(record.clone());
+        events.wake();
+
+        default_hook(info);
+
+        // Returning aborts on the phone, `catch` never sees the panic
+        if cfg!(panic = "abort") {
+            match CRASH_SCREEN.get() {
+                Some((ui_thread, show)) if *ui_thread == thread::current().id() => show(&record),
+                // Until the UI thread shows the crash, see `Phone::event_loop`
+                Some(_) => loop {
+                    thread::park();
+                },
+                None => {},
+            }
+        }
+    }));
+}
+
+/// Called from the UI thread, for builds which abort on panic instead of unwinding to `catch`.
+/// `show` draws the crash from the panic hook of the UI thread, and should not return, e.g. reboot.
+pub fn install_crash_screen(show: impl Fn(&CrashRecord) + Send + Sync + 'static) {
+    CRASH_SCREEN.set((thread::current().id(), Box::new(show))).ok();
+}
+
+/// Whether a thread panicked since the hook was installed, see `Phone::event_loop`
+pub fn panicked() -> bool {
+    PANIC.lock().unwrap_or_else(PoisonError::into_inner).is_some()
+}
+
+/// Runs until a panic or an error, which is returned once saved
+pub fn catch<F: FnOnce() -> anyhow::Result<()>>(run: F) -> Option<CrashRecord> {
+    let result = panic::catch_unwind(AssertUnwindSafe(run));
+    let panic = PANIC.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+    // Panics were saved by the hook
+    match (result, panic) {
+        (Ok(Ok(())), None) => None,
+        (_, Some(record)) => Some(record),
+        (Ok(Err(error)), None) => {
+            let record = CrashRecord::new(&error.to_string(), None);
+            save(&record);
+            Some(record)
+        },
+        (Err(_), None) => Some(CrashRecord::new("Unknown panic", None)),
+    }
+}
+
+fn save(record: &CrashRecord) {
+    error!("Crashed: {}", record);
+
+    let root = STORAGE_ROOT
+        .get()
+        .and_then(|root| root.lock().unwrap_or_else(PoisonError::into_inner).clone());
+
+    let Some(root) = root else {
+        warn!("Crash not saved, no SD card");
+        return;
+    };
+
+    if let Err(error) = record.save(&StorageRoot(&root)) {
+        warn!("Could not save the crash record: {}", error);
+    }
+}
+
+/// The storage of the phone may not be reachable anymore
+struct StorageRoot<'a>(&'a Path);
+
+impl Storage for StorageRoot<'_> {
+    fn root(&self) -> &Path {
+        self.0
+    }
+}
+
+/// Shows the crash until the reboot button is tapped, the touch thread is gone as well, or `timeout` elapses
+pub fn wait_for_reboot<B: Backend>(terminal: &mut Terminal<B>, events: &Receiver<PhoneEvent>, record: &CrashRecord, timeout: Duration) -> anyhow::Result<()> {
+    let mut reboot_rect = Rect::default();
+    let shown = Instant::now();
+
+    while shown.elapsed() < timeout {
+        // Only the changed cells are pushed, redrawing keeps the simulator window responsive
+        terminal.draw(|frame| reboot_rect = record.render(frame))?;
+
+        match events.recv_timeout(CRASH_REDRAW_PERIOD) {
+            Ok(PhoneEvent::Gesture(Gesture::Tap(position))) if reboot_rect.contains(position) => break,
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    info!("Rebooting");
+
+    Ok(())
+}
+
+impl Phone {
+    /// Tells the user about a crash of the previous boot, the details stay in the crash log
+    pub fn report_last_crash(&mut self) -> anyhow::Result<()> {
+        let Some(storage) = self.phone_data.storage.as_deref() else {
+            return Ok(());
+        };
+
+        if let Some(record) = CrashRecord::take_last(storage)? {
+            let message = format!("{}\n\nSee {} on the SD card", record, CRASH_LOG_PATH);
+            self.phone_data.show_dialog(Dialog::alert("The phone restarted unexpectedly", &message));
+        }
+
+        Ok(())
+    }
+}
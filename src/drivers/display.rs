@@ -0,0 +1,108 @@
+use std::path::Path;
+use log::info;
+use serde::{Deserialize, Serialize};
+use crate::platform::Storage;
+
+/// Panel model of the board, set by the user as it cannot be read back from the write-only bus
+pub const DISPLAY_PATH: &str = "display.json";
+
+/// Cell size of the default mousefood font
+pub const FONT_WIDTH: u16 = 6;
+pub const FONT_HEIGHT: u16 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum DisplayModel {
+    /// 240x320
+    #[default]
+    Ili9341,
+    /// 240x320
+    St7789,
+    /// 240x240
+    St7789Square,
+    /// 320x480
+    Ili9488,
+}
+
+impl DisplayModel {
+    /// Panel resolution, in its native portrait orientation
+    pub fn pixel_size(&self) -> (u16, u16) {
+        match self {
+            DisplayModel::Ili9341 => (240, 320),
+            DisplayModel::St7789 => (240, 320),
+            DisplayModel::St7789Square => (240, 240),
+            DisplayModel::Ili9488 => (320, 480),
+        }
+    }
+
+    pub fn load(storage: &dyn Storage) -> anyhow::Result<Option<Self>> {
+        match storage.read_to_string(Path::new(DISPLAY_PATH))? {
+            Some(content) => {
+                let model = serde_json::from_str(&content)?;
+                info!("Display model {:?} loaded", model);
+                Ok(Some(model))
+            },
+            None => Ok(None)
+        }
+    }
+}
+
+/// Screen orientation, the panel itself being portrait
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ScreenOrientation {
+    #[default]
+    Portrait,
+    /// Rotated by 90°, see `drivers::panel`
+    Landscape,
+}
+
+/// What the UI is drawn on
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Screen {
+    pub model: DisplayModel,
+    pub orientation: ScreenOrientation,
+}
+
+impl Screen {
+    pub fn new(model: DisplayModel, orientation: ScreenOrientation) -> Self {
+        Screen {
+            model,
+            orientation,
+        }
+    }
+
+    /// Cell grid, as many characters of the font as fit on the screen
+    pub fn size(&self) -> (u16, u16) {
+        let (pixel_width, pixel_height) = self.pixel_size();
+
+        (pixel_width / FONT_WIDTH, pixel_height / FONT_HEIGHT)
+    }
+
+    pub fn pixel_size(&self) -> (u16, u16) {
+        let (width, height) = self.model.pixel_size();
+
+        match self.orientation {
+            ScreenOrientation::Portrait => (width, height),
+            ScreenOrientation::Landscape => (height, width),
+        }
+    }
+
+    /// Maps a pixel of the portrait panel to the screen
+    pub fn rotate(&self, x: f32, y: f32) -> (f32, f32) {
+        let (panel_width, _) = self.model.pixel_size();
+
+        match self.orientation {
+            ScreenOrientation::Portrait => (x, y),
+            ScreenOrientation::Landscape => (y, (panel_width - 1) as f32 - x),
+        }
+    }
+
+    /// Maps a pixel of the screen back to the portrait panel
+    pub fn unrotate(&self, x: f32, y: f32) -> (f32, f32) {
+        let (panel_width, _) = self.model.pixel_size();
+
+        match self.orientation {
+            ScreenOrientation::Portrait => (x, y),
+            ScreenOrientation::Landscape => ((panel_width - 1) as f32 - y, x),
+        }
+    }
+}
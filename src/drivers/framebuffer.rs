@@ -0,0 +1,117 @@
+use std::convert::Infallible;
+use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Pixel, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use crate::drivers::display::{FONT_HEIGHT, FONT_WIDTH};
+
+/// Copy of the screen in RAM, pushing only the cells that changed to the display on flush.
+/// Saves SPI transfers, and thus power and time the SD card waits for the bus.
+pub struct DirtyFramebuffer<'display, D: DrawTarget<Color = Rgb565> + OriginDimensions> {
+    display: &'display mut D,
+    size: Size,
+    pixels: Vec<Rgb565>,
+    /// One flag per font cell
+    dirty: Vec<bool>,
+    columns: usize,
+}
+
+impl<'display, D: DrawTarget<Color = Rgb565> + OriginDimensions> DirtyFramebuffer<'display, D> {
+    /// Everything is dirty at first, as the display content is unknown
+    pub fn new(display: &'display mut D) -> Self {
+        let size = display.size();
+        let columns = size.width.div_ceil(FONT_WIDTH as u32) as usize;
+        let rows = size.height.div_ceil(FONT_HEIGHT as u32) as usize;
+
+        DirtyFramebuffer {
+            display,
+            size,
+            pixels: vec![Rgb565::BLACK; (size.width * size.height) as usize],
+            dirty: vec![true; columns * rows],
+            columns,
+        }
+    }
+
+    pub fn display(&self) -> &D {
+        self.display
+    }
+
+    /// Pushes each run of dirty cells of a row at once, returns the number of cells pushed
+    pub fn flush(&mut self) -> Result<usize, D::Error> {
+        let mut pushed = 0;
+
+        for (row, cells) in self.dirty.chunks(self.columns).enumerate() {
+            let mut column = 0;
+
+            while column < self.columns {
+                if !cells[column] {
+                    column += 1;
+                    continue;
+                }
+
+                let start = column;
+                while column < self.columns && cells[column] {
+                    column += 1;
+                }
+
+                let x = start as u32 * FONT_WIDTH as u32;
+                let y = row as u32 * FONT_HEIGHT as u32;
+
+                let area = Rectangle {
+                    top_left: Point::new(x as i32, y as i32),
+                    size: Size {
+                        width: ((column - start) as u32 * FONT_WIDTH as u32).min(self.size.width - x),
+                        height: (FONT_HEIGHT as u32).min(self.size.height - y),
+                    },
+                };
+
+                let width = self.size.width as usize;
+                let pixels = &self.pixels;
+                let colors = (area.top_left.y as usize..area.top_left.y as usize + area.size.height as usize)
+                    .flat_map(|y| pixels[y * width + area.top_left.x as usize..][..area.size.width as usize].iter().copied());
+
+                self.display.fill_contiguous(&area, colors)?;
+                pushed += column - start;
+            }
+        }
+
+        self.dirty.fill(false);
+
+        Ok(pushed)
+    }
+
+    fn set_pixel(&mut self, point: Point, color: Rgb565) {
+        if point.x < 0 || point.y < 0 || point.x as u32 >= self.size.width || point.y as u32 >= self.size.height {
+            return;
+        }
+
+        let (x, y) = (point.x as usize, point.y as usize);
+        let index = y * self.size.width as usize + x;
+
+        if self.pixels[index] != color {
+            self.pixels[index] = color;
+            self.dirty[(y / FONT_HEIGHT as usize) * self.columns + x / FONT_WIDTH as usize] = true;
+        }
+    }
+}
+
+impl<D: DrawTarget<Color = Rgb565> + OriginDimensions> OriginDimensions for DirtyFramebuffer<'_, D> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<D: DrawTarget<Color = Rgb565> + OriginDimensions> DrawTarget for DirtyFramebuffer<'_, D> {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set_pixel(point, color);
+        }
+
+        Ok(())
+    }
+}
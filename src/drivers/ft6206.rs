@@ -1,46 +1,30 @@
+use std::time::Duration;
 use esp_idf_svc::hal::delay::BLOCK;
-use esp_idf_svc::hal::gpio::{Gpio21, Gpio22};
+use esp_idf_svc::hal::gpio::{AnyInputPin, Gpio21, Gpio22};
 use esp_idf_svc::hal::i2c::{I2cConfig, I2cDriver, I2C0};
 use esp_idf_svc::hal::units::KiloHertz;
 use log::{info, warn};
+use crate::drivers::interrupt::TouchInterrupt;
+use crate::drivers::touch::{TouchEvent, TouchPoint};
 
 pub const FT6206_ADDR: u8 = 0x38;
 pub const FT6206_REG_MODE: u8 = 0x00;
 pub const FT6206_REG_CTRL: u8 = 0x86;
-pub const FT6206_REG_GEST: u8 = 0x01;
 #[allow(dead_code)]
 pub const FT6206_REG_STATUS: u8 = 0x02;
 pub const FT6206_REG_TOUCH1_XH: u8 = 0x03;
 pub const FT6206_REG_NUM_TOUCHES: u8 = 0x02;
-
-#[derive(Debug, Clone, Copy)]
-pub struct TouchPoint {
-    pub x: u16,
-    pub y: u16,
-    pub id: u8,
-    pub event: Option<TouchEvent>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum TouchEvent {
-    Press,
-    Move,
-    Release,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum TouchGesture {
-    Move,
-    ZoomIn,
-    ZoomOut,
-}
+pub const FT6206_REG_G_MODE: u8 = 0xA4;
 
 pub struct FT6206 {
     i2c: I2cDriver<'static>,
+    /// Pulled low on each new touch report
+    interrupt: Option<TouchInterrupt>,
 }
 
 impl FT6206 {
-    pub fn new(i2c: I2C0, sda_i2c: Gpio21, scl: Gpio22) -> Result<Self, anyhow::Error> {
+    /// Without `interrupt_pin`, the controller is polled
+    pub fn new(i2c: I2C0, sda_i2c: Gpio21, scl: Gpio22, interrupt_pin: Option<AnyInputPin>) -> Result<Self, anyhow::Error> {
         let i2c_config = I2cConfig::new().baudrate(KiloHertz::from(400).into());
         let i2c_driver = I2cDriver::new(
             i2c,
@@ -49,25 +33,57 @@ impl FT6206 {
             &i2c_config
         )?;
 
+        let interrupt = match interrupt_pin {
+            Some(pin) => Some(TouchInterrupt::new(pin)?),
+            None => None,
+        };
+
         let mut ft = Self {
-            i2c: i2c_driver
+            i2c: i2c_driver,
+            interrupt,
         };
 
+        ft.configure()?;
+
+        info!("FT6206 touch controller initialized");
+        Ok(ft)
+    }
+
+    /// Detects the controller and sets its registers, also used to recover from I2C errors
+    pub fn configure(&mut self) -> Result<(), anyhow::Error> {
         // Check if device is connected by reading chip vendor ID
         let mut id_buf = [0u8; 1];
-        if let Err(e) = ft.i2c.read(FT6206_ADDR, &mut id_buf, BLOCK) {
+        if let Err(e) = self.i2c.read(FT6206_ADDR, &mut id_buf, BLOCK) {
             warn!("Could not detect FT6206 touch controller: {}", e);
             return Err(anyhow::anyhow!("FT6206 not found"));
         }
 
         // Configure the FT6206 in normal operating mode
-        ft.write_register(FT6206_REG_MODE, 0x00)?;
+        self.write_register(FT6206_REG_MODE, 0x00)?;
 
-        // Set interrupt trigger mode to polling (0) instead of triggered (1)
-        ft.write_register(FT6206_REG_CTRL, 0x00)?;
+        // Stay in active mode when nothing touches (0), instead of switching to the slower monitor mode (1)
+        self.write_register(FT6206_REG_CTRL, 0x00)?;
 
-        info!("FT6206 touch controller initialized");
-        Ok(ft)
+        // Pulse INT on each report when it is wired, keep it level-triggered otherwise
+        let interrupt_mode = match self.interrupt.is_some() {
+            true => 0x01,
+            false => 0x00,
+        };
+        self.write_register(FT6206_REG_G_MODE, interrupt_mode)?;
+
+        Ok(())
+    }
+
+    pub fn has_interrupt(&self) -> bool {
+        self.interrupt.is_some()
+    }
+
+    /// Blocks until INT is pulled low or the timeout elapses, returns immediately when polling
+    pub fn wait_for_interrupt(&mut self, timeout: Duration) -> Result<bool, anyhow::Error> {
+        match &mut self.interrupt {
+            Some(interrupt) => interrupt.wait(timeout),
+            None => Ok(false),
+        }
     }
 
     fn read_register(&mut self, reg: u8, buffer: &mut [u8]) -> Result<(), anyhow::Error> {
@@ -118,18 +134,4 @@ impl FT6206 {
 
         Ok(points)
     }
-
-    pub fn get_gesture(&mut self) -> Result<Option<TouchGesture>, anyhow::Error> {
-        let mut data = [0u8; 1];
-        self.read_register(FT6206_REG_GEST, &mut data)?;
-
-        let gesture = match data[0] {
-            0x10 => Some(TouchGesture::Move),
-            0x18 => Some(TouchGesture::ZoomIn),
-            0x14 => Some(TouchGesture::ZoomOut),
-            _ => None
-        };
-
-        Ok(gesture)
-    }
-}
\ No newline at end of file
+}
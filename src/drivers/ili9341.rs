@@ -1,2 +0,0 @@
-pub const WIDTH: u16 = 40;
-pub const HEIGHT: u16 = 32;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use std::num::NonZeroU32;
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+use esp_idf_svc::hal::delay::TickType;
+use esp_idf_svc::hal::gpio::{AnyInputPin, Input, InterruptType, PinDriver};
+use esp_idf_svc::hal::task::notification::Notification;
+
+/// Interrupt line of a touch controller, pulled low when touched.
+/// Pulled up by the board, as input only pins such as the GPIO 36 of the Cheap Yellow Display have no internal pull-up.
+pub struct TouchInterrupt {
+    pin: PinDriver<'static, AnyInputPin, Input>,
+    /// Notifies the task which created it, so it is created by the waiting thread, see `TouchInterrupt::wait`
+    waiter: Option<(ThreadId, Notification)>,
+}
+
+impl TouchInterrupt {
+    /// Built on the main task, the ISR is only subscribed by the first `wait`
+    pub fn new(pin: AnyInputPin) -> Result<Self, anyhow::Error> {
+        let mut pin = PinDriver::input(pin)?;
+        pin.set_interrupt_type(InterruptType::NegEdge)?;
+
+        Ok(TouchInterrupt {
+            pin,
+            waiter: None,
+        })
+    }
+
+    /// Blocks until the line is pulled low or the timeout elapses, returns whether it was pulled low
+    pub fn wait(&mut self, timeout: Duration) -> Result<bool, anyhow::Error> {
+        let current = thread::current().id();
+
+        if self.waiter.as_ref().map_or(true, |(thread, _)| *thread != current) {
+            self.subscribe(current)?;
+        }
+
+        // The driver disables the interrupt each time it fires
+        self.pin.enable_interrupt()?;
+
+        let Some((_, notification)) = &self.waiter else {
+            return Ok(false);
+        };
+
+        Ok(notification.wait(TickType::from(timeout).ticks()).is_some())
+    }
+
+    /// Until the next `wait`, e.g. while the controller drives the line itself
+    pub fn disable(&mut self) -> Result<(), anyhow::Error> {
+        self.pin.disable_interrupt()?;
+        Ok(())
+    }
+
+    /// Wakes the calling thread from the ISR, instead of the one of a previous `wait`
+    fn subscribe(&mut self, thread: ThreadId) -> Result<(), anyhow::Error> {
+        let notification = Notification::new();
+        let notifier = notification.notifier();
+
+        // Safety: the callback only notifies the waiting task, which is allowed from an ISR
+        unsafe {
+            self.pin.subscribe(move || {
+                notifier.notify_and_yield(NonZeroU32::new(1).unwrap());
+            })?;
+        }
+
+        self.waiter = Some((thread, notification));
+
+        Ok(())
+    }
+}
@@ -1,2 +1,13 @@
+#[cfg(feature = "esp")]
 pub mod ft6206;
-pub mod ili9341;
\ No newline at end of file
+#[cfg(feature = "esp")]
+pub mod xpt2046;
+#[cfg(feature = "esp")]
+pub mod interrupt;
+#[cfg(feature = "esp")]
+pub mod panel;
+pub mod display;
+pub mod framebuffer;
+pub mod resistive;
+pub mod shared_display;
+pub mod touch;
\ No newline at end of file
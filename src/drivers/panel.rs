@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics::pixelcolor::{Rgb565, Rgb666};
+use embedded_graphics::prelude::{Dimensions, DrawTarget, OriginDimensions, Pixel, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use mipidsi::models::{ILI9341Rgb565, ILI9488Rgb666, ST7789};
+use mipidsi::options::{ColorInversion, ColorOrder, Orientation, Rotation};
+use mipidsi::{Builder, Display};
+use crate::drivers::display::{DisplayModel, ScreenOrientation};
+
+/// Any supported display controller, chosen at runtime so that every board runs the same firmware
+pub enum Panel<DI: WriteOnlyDataCommand, RST: OutputPin> {
+    Ili9341(Display<DI, ILI9341Rgb565, RST>),
+    St7789(Display<DI, ST7789, RST>),
+    /// Only takes 18 bits colors over SPI
+    Ili9488(Display<DI, ILI9488Rgb666, RST>),
+}
+
+impl<DI: WriteOnlyDataCommand, RST: OutputPin> Panel<DI, RST> {
+    pub fn new(model: DisplayModel, di: DI, rst: RST, delay: &mut impl DelayNs) -> anyhow::Result<Self> {
+        let (width, height) = model.pixel_size();
+
+        let panel = match model {
+            DisplayModel::Ili9341 => Builder::new(ILI9341Rgb565, di)
+                .reset_pin(rst)
+                .color_order(ColorOrder::Bgr)
+                .init(delay)
+                .map(Panel::Ili9341),
+            DisplayModel::St7789 | DisplayModel::St7789Square => Builder::new(ST7789, di)
+                .reset_pin(rst)
+                .display_size(width, height)
+                .invert_colors(ColorInversion::Inverted)
+                .init(delay)
+                .map(Panel::St7789),
+            DisplayModel::Ili9488 => Builder::new(ILI9488Rgb666, di)
+                .reset_pin(rst)
+                .color_order(ColorOrder::Bgr)
+                .init(delay)
+                .map(Panel::Ili9488),
+        };
+
+        panel.map_err(|error| anyhow!("Could not initialize the {:?} display: {:?}", model, error))
+    }
+
+    pub fn set_orientation(&mut self, orientation: ScreenOrientation) -> anyhow::Result<()> {
+        // Must match `Screen::rotate`
+        let orientation = match orientation {
+            ScreenOrientation::Portrait => Orientation::new(),
+            ScreenOrientation::Landscape => Orientation::new().rotate(Rotation::Deg90),
+        };
+
+        let result = match self {
+            Panel::Ili9341(display) => display.set_orientation(orientation),
+            Panel::St7789(display) => display.set_orientation(orientation),
+            Panel::Ili9488(display) => display.set_orientation(orientation),
+        };
+
+        result.map_err(|error| anyhow!("Could not rotate the display: {:?}", error))
+    }
+}
+
+impl<DI: WriteOnlyDataCommand, RST: OutputPin> OriginDimensions for Panel<DI, RST> {
+    fn size(&self) -> Size {
+        match self {
+            Panel::Ili9341(display) => display.size(),
+            Panel::St7789(display) => display.size(),
+            Panel::Ili9488(display) => display.size(),
+        }
+    }
+}
+
+impl<DI: WriteOnlyDataCommand, RST: OutputPin> DrawTarget for Panel<DI, RST> {
+    type Color = Rgb565;
+    type Error = <Display<DI, ILI9341Rgb565, RST> as DrawTarget>::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        match self {
+            Panel::Ili9341(display) => display.draw_iter(pixels),
+            Panel::St7789(display) => display.draw_iter(pixels),
+            Panel::Ili9488(display) => display.draw_iter(
+                pixels.into_iter().map(|Pixel(point, color)| Pixel(point, Rgb666::from(color)))
+            ),
+        }
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        match self {
+            Panel::Ili9341(display) => display.fill_contiguous(area, colors),
+            Panel::St7789(display) => display.fill_contiguous(area, colors),
+            Panel::Ili9488(display) => display.fill_contiguous(area, colors.into_iter().map(Rgb666::from)),
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        match self {
+            Panel::Ili9341(display) => display.fill_solid(area, color),
+            Panel::St7789(display) => display.fill_solid(area, color),
+            Panel::Ili9488(display) => display.fill_solid(area, Rgb666::from(color)),
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::drivers::display::DisplayModel;
+use crate::drivers::touch::{TouchEvent, TouchPoint};
+
+/// Full scale of the 12 bits ADC of resistive controllers
+pub const RESISTIVE_MAX: u16 = 4095;
+
+/// One conversion of a resistive controller
+#[derive(Debug, Clone, Copy)]
+pub struct ResistiveSample {
+    pub x: u16,
+    pub y: u16,
+    /// Pressure, grows with the force of the finger
+    pub z: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResistiveConfig {
+    /// Minimum pressure for a finger to be put down
+    pub press_threshold: u16,
+    /// Pressure under which a finger down is lifted, lower than `press_threshold` to avoid flickering
+    pub release_threshold: u16,
+    /// Conversions averaged into a single touch point
+    pub samples: usize,
+    /// Panel the touch screen sits on
+    pub panel: DisplayModel,
+}
+
+impl Default for ResistiveConfig {
+    fn default() -> Self {
+        ResistiveConfig {
+            press_threshold: 600,
+            release_threshold: 400,
+            samples: 5,
+            panel: DisplayModel::default(),
+        }
+    }
+}
+
+/// Turns noisy resistive conversions into the touch points of a capacitive controller
+pub struct ResistiveFilter {
+    config: ResistiveConfig,
+    /// Last point reported while the finger is down, where it is released
+    pressed: Option<TouchPoint>,
+}
+
+impl ResistiveFilter {
+    pub fn new(config: ResistiveConfig) -> Self {
+        ResistiveFilter {
+            config,
+            pressed: None,
+        }
+    }
+
+    pub fn config(&self) -> &ResistiveConfig {
+        &self.config
+    }
+
+    /// Coordinates are scaled to the panel resolution, so that the default calibration roughly fits
+    pub fn update(&mut self, samples: &[ResistiveSample]) -> Vec<TouchPoint> {
+        // Conversions taken while the finger is lifting are way off
+        let samples: Vec<&ResistiveSample> = samples
+            .iter()
+            .filter(|sample| sample.z >= self.config.release_threshold)
+            .collect();
+
+        let threshold = match self.pressed {
+            Some(_) => self.config.release_threshold,
+            None => self.config.press_threshold,
+        };
+
+        // Thresholds of 0 let an empty conversion through otherwise
+        if samples.is_empty() {
+            return self.release();
+        }
+
+        let pressure = samples.iter().map(|sample| sample.z as u32).sum::<u32>() / samples.len() as u32;
+
+        if pressure < threshold as u32 {
+            return self.release();
+        }
+
+        let x = trimmed_mean(samples.iter().map(|sample| sample.x).collect());
+        let y = trimmed_mean(samples.iter().map(|sample| sample.y).collect());
+
+        let event = match self.pressed {
+            Some(_) => TouchEvent::Move,
+            None => TouchEvent::Press,
+        };
+
+        let (pixel_width, pixel_height) = self.config.panel.pixel_size();
+
+        let touch = TouchPoint {
+            x: (x as u32 * pixel_width as u32 / (RESISTIVE_MAX as u32 + 1)) as u16,
+            y: (y as u32 * pixel_height as u32 / (RESISTIVE_MAX as u32 + 1)) as u16,
+            id: 0,
+            event: Some(event),
+        };
+
+        self.pressed = Some(touch);
+
+        vec![touch]
+    }
+
+    /// Reports the lift-off once, like the FT6206 does, at the last position of the finger
+    fn release(&mut self) -> Vec<TouchPoint> {
+        match self.pressed.take() {
+            Some(touch) => vec![TouchPoint {
+                event: Some(TouchEvent::Release),
+                ..touch
+            }],
+            None => vec![],
+        }
+    }
+}
+
+/// Mean without the extreme values, which are the most likely to be noise
+fn trimmed_mean(mut values: Vec<u16>) -> u16 {
+    values.sort_unstable();
+
+    if values.len() >= 3 {
+        values.remove(0);
+        values.pop();
+    }
+
+    (values.iter().map(|value| *value as u32).sum::<u32>() / values.len() as u32) as u16
+}
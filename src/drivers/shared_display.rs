@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Pixel, Size};
+use embedded_graphics::primitives::Rectangle;
+
+/// Display drawn by the UI thread, and by the panic hook when it panics, see `crash::install_crash_screen`
+pub struct SharedDisplay<D>(Arc<Mutex<D>>);
+
+impl<D> Clone for SharedDisplay<D> {
+    fn clone(&self) -> Self {
+        SharedDisplay(self.0.clone())
+    }
+}
+
+impl<D> SharedDisplay<D> {
+    pub fn new(display: D) -> Self {
+        SharedDisplay(Arc::new(Mutex::new(display)))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, D> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// `None` while the display is drawn, e.g. by a thread which panicked in the middle of it
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, D>> {
+        match self.0.try_lock() {
+            Ok(display) => Some(display),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+}
+
+impl<D: OriginDimensions> OriginDimensions for SharedDisplay<D> {
+    fn size(&self) -> Size {
+        self.lock().size()
+    }
+}
+
+impl<D: DrawTarget + OriginDimensions> DrawTarget for SharedDisplay<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.lock().draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.lock().fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.lock().fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.lock().clear(color)
+    }
+}
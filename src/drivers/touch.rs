@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TouchPoint {
+    pub x: u16,
+    pub y: u16,
+    pub id: u8,
+    pub event: Option<TouchEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TouchEvent {
+    Press,
+    Move,
+    Release,
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+use esp_idf_svc::hal::delay::Ets;
+use esp_idf_svc::hal::gpio::{AnyInputPin, AnyOutputPin, Input, Output, PinDriver};
+use log::info;
+use crate::drivers::interrupt::TouchInterrupt;
+use crate::drivers::resistive::{ResistiveConfig, ResistiveFilter, ResistiveSample, RESISTIVE_MAX};
+use crate::drivers::touch::TouchPoint;
+
+/// Control bytes, 12 bits differential conversions
+pub const XPT2046_CMD_X: u8 = 0xD0;
+pub const XPT2046_CMD_Y: u8 = 0x90;
+pub const XPT2046_CMD_Z1: u8 = 0xB0;
+pub const XPT2046_CMD_Z2: u8 = 0xC0;
+
+/// Half a clock period, the XPT2046 takes up to 2.5 MHz
+const SPI_HALF_PERIOD_US: u32 = 1;
+
+/// Bit-banged SPI bus (mode 0) of the XPT2046, as the display and the SD card use both SPI hosts of the ESP32
+pub struct XPT2046Spi {
+    clk: PinDriver<'static, AnyOutputPin, Output>,
+    mosi: PinDriver<'static, AnyOutputPin, Output>,
+    miso: PinDriver<'static, AnyInputPin, Input>,
+    cs: PinDriver<'static, AnyOutputPin, Output>,
+}
+
+impl XPT2046Spi {
+    pub fn new(clk: AnyOutputPin, mosi: AnyOutputPin, miso: AnyInputPin, cs: AnyOutputPin) -> Result<Self, anyhow::Error> {
+        let mut spi = XPT2046Spi {
+            clk: PinDriver::output(clk)?,
+            mosi: PinDriver::output(mosi)?,
+            miso: PinDriver::input(miso)?,
+            cs: PinDriver::output(cs)?,
+        };
+
+        spi.clk.set_low()?;
+        spi.cs.set_high()?;
+
+        Ok(spi)
+    }
+
+    /// Full duplex, most significant bit first
+    pub fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), anyhow::Error> {
+        self.cs.set_low()?;
+
+        for (read_byte, write_byte) in read.iter_mut().zip(write) {
+            *read_byte = 0;
+
+            for bit in (0..8).rev() {
+                self.mosi.set_level(((write_byte >> bit) & 1 == 1).into())?;
+                Ets::delay_us(SPI_HALF_PERIOD_US);
+
+                // Sampled on the rising edge, shifted out on the falling one
+                self.clk.set_high()?;
+                *read_byte = (*read_byte << 1) | self.miso.is_high() as u8;
+                Ets::delay_us(SPI_HALF_PERIOD_US);
+                self.clk.set_low()?;
+            }
+        }
+
+        self.cs.set_high()?;
+
+        Ok(())
+    }
+}
+
+/// Resistive touch controller of the ESP32 "Cheap Yellow Display" boards
+pub struct XPT2046 {
+    spi: XPT2046Spi,
+    filter: ResistiveFilter,
+    /// PENIRQ, pulled low while touched
+    interrupt: Option<TouchInterrupt>,
+}
+
+impl XPT2046 {
+    /// Without `interrupt_pin`, the controller is polled
+    pub fn new(spi: XPT2046Spi, interrupt_pin: Option<AnyInputPin>, config: ResistiveConfig) -> Result<Self, anyhow::Error> {
+        let interrupt = match interrupt_pin {
+            Some(pin) => Some(TouchInterrupt::new(pin)?),
+            None => None,
+        };
+
+        info!("XPT2046 touch controller initialized");
+
+        Ok(XPT2046 {
+            spi,
+            filter: ResistiveFilter::new(config),
+            interrupt,
+        })
+    }
+
+    fn read_channel(&mut self, command: u8) -> Result<u16, anyhow::Error> {
+        let mut buffer = [0u8; 3];
+        self.spi.transfer(&mut buffer, &[command, 0, 0])?;
+
+        // The 12 bits result starts right after the busy bit
+        Ok(((((buffer[1] as u16) << 8) | buffer[2] as u16) >> 3) & RESISTIVE_MAX)
+    }
+
+    fn read_sample(&mut self) -> Result<ResistiveSample, anyhow::Error> {
+        let z1 = self.read_channel(XPT2046_CMD_Z1)?;
+        let z2 = self.read_channel(XPT2046_CMD_Z2)?;
+        let x = self.read_channel(XPT2046_CMD_X)?;
+        let y = self.read_channel(XPT2046_CMD_Y)?;
+
+        Ok(ResistiveSample {
+            x,
+            y,
+            z: (z1 + RESISTIVE_MAX).saturating_sub(z2),
+        })
+    }
+
+    pub fn read_touches(&mut self) -> Result<Vec<TouchPoint>, anyhow::Error> {
+        // PENIRQ goes low during the conversions, which would wake the next wait without a touch
+        if let Some(interrupt) = &mut self.interrupt {
+            interrupt.disable()?;
+        }
+
+        let samples = (0..self.filter.config().samples)
+            .map(|_| self.read_sample())
+            .collect::<Result<Vec<ResistiveSample>, anyhow::Error>>()?;
+
+        Ok(self.filter.update(&samples))
+    }
+
+    pub fn has_interrupt(&self) -> bool {
+        self.interrupt.is_some()
+    }
+
+    /// Blocks until PENIRQ is pulled low or the timeout elapses, returns immediately when polling
+    pub fn wait_for_interrupt(&mut self, timeout: Duration) -> Result<bool, anyhow::Error> {
+        match &mut self.interrupt {
+            Some(interrupt) => interrupt.wait(timeout),
+            None => Ok(false),
+        }
+    }
+}
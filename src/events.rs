@@ -4,12 +4,19 @@ use async_trait::async_trait;
 use log::info;
 use mousefood::prelude::{Position};
 use crate::apps::app::{ClickableArea};
-use crate::drivers::ft6206::{TouchEvent, TouchPoint};
-use crate::drivers::ili9341::{HEIGHT, WIDTH};
+use crate::events::gestures::{Gesture, GestureSubscription, SwipeDirection};
+use crate::navigation::DeepLink;
+use crate::notifications::NotificationEvent;
 use crate::phone::Phone;
+use crate::runtime::block_on;
 use crate::state::PhoneState;
+use crate::ui::widgets::dialog::DialogEvent;
 use crate::ui::widgets::keyboard::{KeyboardEvent};
 
+pub mod calibration;
+pub mod gestures;
+pub mod queue;
+
 #[async_trait]
 pub trait AppEvent: Any + Debug + Send + Sync {
     fn as_any(&self) -> &dyn Any;
@@ -22,10 +29,10 @@ impl<T: Any + Debug + Send + Sync> AppEvent for T {
     }
 }
 
+/// Background work posts its events instead, see `EventSender::post`
 #[derive(Debug)]
 pub enum EventType {
     List(Vec<ClickableArea>),
-    Auto(Box<dyn AppEvent>),
 }
 
 impl Default for EventType {
@@ -34,88 +41,157 @@ impl Default for EventType {
     }
 }
 
+/// Swipes starting this close to the left edge go back
+const BACK_SWIPE_EDGE: u16 = 2;
+/// Swipes down starting this close to the top edge open the notification shade
+const SHADE_SWIPE_EDGE: u16 = 2;
+
 #[derive(Debug)]
 pub enum CoreEvent {
     GoBackToHomepage,
-    LaunchApp(usize)
+    LaunchApp(usize),
+    ScrollAppList(Gesture),
+    /// Previous screen, see `Phone::back`
+    Back,
+    Open(DeepLink),
+    Notification(NotificationEvent),
+    /// Button of the dialog on top, see `PhoneData::show_dialog`
+    Dialog(DialogEvent),
+    DismissToast,
+    /// Deletes the files of the app, see `Phone::clear_app_data`
+    ClearAppData(&'static str),
 }
 
-impl Phone<'_> {
-    pub fn format_touches(&self, touches: &Vec<TouchPoint>) -> Option<Position> {
-        if let Some(touch) = touches.first() {
-            if touch.event != Some(TouchEvent::Press) {
-                return None;
-            } 
-            
-            let mut x = WIDTH - (touch.x * WIDTH / 240);
-            let mut y = HEIGHT - 1 - (touch.y * HEIGHT / 320);
-
-            if x >= WIDTH {
-                x = WIDTH - 1;
-            }
-            if y >= HEIGHT {
-                y = HEIGHT - 1;
-            }
-
-            Some(Position::new(x, y))
-        } else {
-            None
-        }
-    }
-
+impl Phone {
     #[allow(unused_assignments)]
     pub fn handle_touch(&mut self, touch: Position, clickable_areas: &Vec<ClickableArea>) -> anyhow::Result<Option<PhoneState>> {
         let mut touch_succeeded = false;
 
         for clickable_area in clickable_areas {
             let area = &clickable_area.0;
-            let event = &clickable_area.1;
+            let event = clickable_area.1.as_ref();
+
+            if event.as_any().is::<GestureSubscription>() {
+                continue;
+            }
 
             if area.contains(touch) {
                 touch_succeeded = true;
 
-                let state = if let Some(event) = event.as_ref().as_any().downcast_ref::<CoreEvent>() {
-                    info!("{:?}", event);
+                return self.dispatch_event(event);
+            }
+        }
+
+        if !touch_succeeded {
+            info!("Missed {:?}", touch);
+        }
 
-                    let state = match event {
-                        CoreEvent::GoBackToHomepage => PhoneState::Homepage,
-                        CoreEvent::LaunchApp(index) => PhoneState::InApp(*index),
-                    };
+        Ok(None)
+    }
 
-                    Some(state)
-                }
-                else if let Some(event) = event.as_ref().as_any().downcast_ref::<KeyboardEvent>() {
-                    info!("{:?}", event);
+    pub fn handle_gesture(&mut self, gesture: Gesture, clickable_areas: &Vec<ClickableArea>) -> anyhow::Result<Option<PhoneState>> {
+        info!("{:?}", gesture);
+
+        let system_event = match gesture {
+            Gesture::Swipe { from, direction: SwipeDirection::Right } if from.x < BACK_SWIPE_EDGE => Some(CoreEvent::Back),
+            Gesture::Swipe { from, direction: SwipeDirection::Down } if from.y < SHADE_SWIPE_EDGE => Some(CoreEvent::Notification(NotificationEvent::OpenShade)),
+            Gesture::Swipe { direction: SwipeDirection::Up, .. } if self.shade_open => Some(CoreEvent::Notification(NotificationEvent::CloseShade)),
+            _ => None,
+        };
 
-                    self.phone_data.keyboard.as_mut().unwrap().handle_event(event);
+        if let Some(event) = system_event {
+            return self.dispatch_event(&event);
+        }
 
-                    None
+        for clickable_area in clickable_areas {
+            let area = &clickable_area.0;
+
+            if let Some(subscription) = clickable_area.1.as_ref().as_any().downcast_ref::<GestureSubscription>() {
+                if subscription.kind == gesture.kind() && area.contains(gesture.position()) {
+                    let event = (subscription.on_gesture)(gesture);
+                    return self.dispatch_event(event.as_ref());
                 }
-                else {
-                    let state = match self.state {
-                        PhoneState::InApp(index) => self.apps[index].handle_event(&mut self.phone_data, event)?,
-                        _ => None
-                    };
+            }
+        }
 
-                    state
-                };
+        match gesture {
+            Gesture::Tap(position) => self.handle_touch(position, clickable_areas),
+            _ => Ok(None)
+        }
+    }
 
-                return Ok(state);
+    pub fn dispatch_event(&mut self, event: &dyn AppEvent) -> anyhow::Result<Option<PhoneState>> {
+        let state = if let Some(event) = event.as_any().downcast_ref::<CoreEvent>() {
+            info!("{:?}", event);
+
+            match event {
+                CoreEvent::GoBackToHomepage => Some(PhoneState::Homepage),
+                CoreEvent::LaunchApp(index) => Some(PhoneState::InApp(*index)),
+                CoreEvent::ScrollAppList(gesture) => {
+                    self.app_list.handle_gesture(gesture);
+                    None
+                },
+                CoreEvent::Back => {
+                    self.back()?;
+                    None
+                },
+                CoreEvent::Open(link) => self.open(link)?,
+                CoreEvent::Notification(event) => self.handle_notification_event(event)?,
+                CoreEvent::Dialog(event) => self.handle_dialog_event(event)?,
+                CoreEvent::DismissToast => {
+                    self.phone_data.toast = None;
+                    None
+                },
+                CoreEvent::ClearAppData(app_name) => {
+                    self.clear_app_data(app_name)?;
+                    None
+                },
             }
         }
+        else if let Some(event) = event.as_any().downcast_ref::<KeyboardEvent>() {
+            info!("{:?}", event);
 
-        if !touch_succeeded {
-            info!("Missed {:?}", touch);
+            self.phone_data.keyboard.as_mut().unwrap().handle_event(event);
+
+            None
         }
+        else {
+            let state = match self.state {
+                PhoneState::InApp(index) => {
+                    let state = block_on(self.apps[index].handle_event(&mut self.phone_data, event))?;
+                    self.collect_screens(index);
+                    state
+                },
+                _ => None
+            };
 
-        Ok(None)
+            state
+        };
+
+        Ok(state)
     }
     
-    pub fn handle_auto_event(&mut self, event: &Box<dyn AppEvent>) -> anyhow::Result<Option<PhoneState>> {
-        let state = match self.state {
-            PhoneState::InApp(index) => self.apps[index].handle_event(&mut self.phone_data, event)?,
-            _ => None
-        };
+    /// Reaches every app handling this event type, only the foreground one may change the phone state
+    pub fn handle_posted_event(&mut self, event: &dyn AppEvent) -> anyhow::Result<Option<PhoneState>> {
+        // E.g. a deep link posted by a service
+        if event.as_any().is::<CoreEvent>() {
+            return self.dispatch_event(event);
+        }
+
+        let mut state = None;
+
+        for index in 0..self.apps.len() {
+            if self.is_disabled(index) {
+                continue;
+            }
+
+            let app_state = block_on(self.apps[index].handle_event(&mut self.phone_data, event))?;
+            self.collect_screens(index);
+
+            if matches!(self.state, PhoneState::InApp(current) if current == index) {
+                state = app_state;
+            }
+        }
 
         Ok(state)
     }
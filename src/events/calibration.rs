@@ -0,0 +1,134 @@
+use std::path::Path;
+use std::time::Instant;
+use anyhow::anyhow;
+use log::info;
+use mousefood::prelude::Position;
+use serde::{Deserialize, Serialize};
+use crate::drivers::display::{DisplayModel, Screen};
+use crate::drivers::touch::TouchPoint;
+use crate::events::gestures::{Gesture, GestureRecognizer};
+use crate::platform::Storage;
+
+/// Relative to the storage root
+pub const CALIBRATION_PATH: &str = "calibration.json";
+
+/// Affine transform from raw touch controller coordinates to screen pixels:
+/// `x = a * raw_x + b * raw_y + c` and `y = d * raw_x + e * raw_y + f`.
+/// Swapped axes show up as non-zero `b` and `d` coefficients.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TouchCalibration {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for TouchCalibration {
+    fn default() -> Self {
+        TouchCalibration::for_model(DisplayModel::default())
+    }
+}
+
+impl TouchCalibration {
+    /// Mirrored axes, which fits the FT6206 of the ili9341 board
+    pub fn for_model(model: DisplayModel) -> Self {
+        let (pixel_width, pixel_height) = model.pixel_size();
+
+        TouchCalibration {
+            a: -1.0,
+            b: 0.0,
+            c: pixel_width as f32,
+            d: 0.0,
+            e: -1.0,
+            f: (pixel_height - 1) as f32,
+        }
+    }
+
+    /// Solves the transform mapping each raw point to its screen pixel
+    pub fn from_samples(raw: &[(f32, f32); 3], screen: &[(f32, f32); 3]) -> anyhow::Result<Self> {
+        let [(x1, y1), (x2, y2), (x3, y3)] = *raw;
+
+        let determinant = x1 * (y2 - y3) + x2 * (y3 - y1) + x3 * (y1 - y2);
+
+        if determinant.abs() < 1.0 {
+            return Err(anyhow!("Calibration points are aligned"));
+        }
+
+        // Cramer's rule, once per screen axis
+        let solve = |s1: f32, s2: f32, s3: f32| {
+            (
+                (s1 * (y2 - y3) + s2 * (y3 - y1) + s3 * (y1 - y2)) / determinant,
+                (x1 * (s2 - s3) + x2 * (s3 - s1) + x3 * (s1 - s2)) / determinant,
+                (x1 * (y2 * s3 - y3 * s2) + x2 * (y3 * s1 - y1 * s3) + x3 * (y1 * s2 - y2 * s1)) / determinant,
+            )
+        };
+
+        let (a, b, c) = solve(screen[0].0, screen[1].0, screen[2].0);
+        let (d, e, f) = solve(screen[0].1, screen[1].1, screen[2].1);
+
+        Ok(TouchCalibration { a, b, c, d, e, f })
+    }
+
+    pub fn pixel(&self, touch: &TouchPoint) -> (f32, f32) {
+        let (x, y) = (touch.x as f32, touch.y as f32);
+
+        (self.a * x + self.b * y + self.c, self.d * x + self.e * y + self.f)
+    }
+
+    /// Maps a raw point to a screen cell, points outside the screen stick to its edges
+    pub fn position(&self, touch: &TouchPoint, screen: Screen) -> Position {
+        let (x, y) = self.pixel(touch);
+        let (x, y) = screen.rotate(x, y);
+
+        let (width, height) = screen.size();
+        let (pixel_width, pixel_height) = screen.pixel_size();
+
+        let x = (x.max(0.0) as u32 * width as u32 / pixel_width as u32).min(width as u32 - 1);
+        let y = (y.max(0.0) as u32 * height as u32 / pixel_height as u32).min(height as u32 - 1);
+
+        Position::new(x as u16, y as u16)
+    }
+
+    pub fn load(storage: &dyn Storage) -> anyhow::Result<Option<Self>> {
+        match storage.read_to_string(Path::new(CALIBRATION_PATH))? {
+            Some(content) => {
+                info!("Touch calibration loaded");
+                Ok(Some(serde_json::from_str(&content)?))
+            },
+            None => Ok(None)
+        }
+    }
+
+    pub fn save(&self, storage: &dyn Storage) -> anyhow::Result<()> {
+        storage.write(Path::new(CALIBRATION_PATH), serde_json::to_string(self)?.as_bytes())?;
+
+        info!("Touch calibration saved");
+
+        Ok(())
+    }
+}
+
+/// Touch state shared between the touch thread and the phone
+#[derive(Debug, Default)]
+pub struct TouchContext {
+    pub calibration: TouchCalibration,
+    pub screen: Screen,
+    /// Raw point of the last finger put down, used to calibrate
+    pub last_press: Option<TouchPoint>,
+}
+
+impl TouchContext {
+    /// Feeds a read to the recognizer with the current calibration
+    pub fn recognize(&mut self, recognizer: &mut GestureRecognizer, touches: &[TouchPoint], now: Instant) -> Vec<Gesture> {
+        if recognizer.is_idle() {
+            if let Some(touch) = touches.first() {
+                self.last_press = Some(*touch);
+            }
+        }
+
+        recognizer.set_calibration(self.calibration, self.screen);
+        recognizer.update(touches, now)
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, Instant};
+use mousefood::prelude::{Position, Rect};
+use crate::apps::app::ClickableArea;
+use crate::drivers::display::Screen;
+use crate::drivers::touch::{TouchEvent, TouchPoint};
+use crate::events::AppEvent;
+use crate::events::calibration::TouchCalibration;
+
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(600);
+const DOUBLE_TAP_INTERVAL: Duration = Duration::from_millis(300);
+const SWIPE_MAX_DURATION: Duration = Duration::from_millis(400);
+/// In cells
+const SWIPE_MIN_DISTANCE: u16 = 4;
+/// In cells, smaller moves are still considered a tap
+const DRAG_THRESHOLD: u16 = 1;
+/// Finger distance ratio, in percent, between two pinch steps
+const PINCH_STEP: u32 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Tap(Position),
+    /// Follows the `Tap` of the second tap, which still reaches the buttons: tapping a key twice quickly types it twice.
+    /// Apps telling both apart subscribe to it, see `ClickableArea::gesture`.
+    DoubleTap(Position),
+    LongPress(Position),
+    /// Emitted each time the finger moves to another cell
+    Drag { from: Position, to: Position },
+    /// Quick drag, emitted on release
+    Swipe { from: Position, direction: SwipeDirection },
+    Pinch { center: Position, zoom: Zoom },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureKind {
+    Tap,
+    DoubleTap,
+    LongPress,
+    Drag,
+    Swipe,
+    Pinch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zoom {
+    In,
+    Out,
+}
+
+impl Gesture {
+    pub fn kind(&self) -> GestureKind {
+        match self {
+            Gesture::Tap(_) => GestureKind::Tap,
+            Gesture::DoubleTap(_) => GestureKind::DoubleTap,
+            Gesture::LongPress(_) => GestureKind::LongPress,
+            Gesture::Drag { .. } => GestureKind::Drag,
+            Gesture::Swipe { .. } => GestureKind::Swipe,
+            Gesture::Pinch { .. } => GestureKind::Pinch,
+        }
+    }
+
+    /// Where the gesture started
+    pub fn position(&self) -> Position {
+        match self {
+            Gesture::Tap(position) | Gesture::DoubleTap(position) | Gesture::LongPress(position) => *position,
+            Gesture::Drag { from, .. } | Gesture::Swipe { from, .. } => *from,
+            Gesture::Pinch { center, .. } => *center,
+        }
+    }
+}
+
+/// Listens to a gesture over an area, rendered next to the other clickable areas
+pub struct GestureSubscription {
+    pub kind: GestureKind,
+    pub on_gesture: fn(Gesture) -> Box<dyn AppEvent>,
+}
+
+impl Debug for GestureSubscription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GestureSubscription({:?})", self.kind)
+    }
+}
+
+impl ClickableArea {
+    pub fn gesture(area: Rect, kind: GestureKind, on_gesture: fn(Gesture) -> Box<dyn AppEvent>) -> Self {
+        ClickableArea(area, Box::new(GestureSubscription { kind, on_gesture }))
+    }
+}
+
+enum RecognizerState {
+    Idle,
+    Pressed {
+        start: Instant,
+        origin: Position,
+        last: Position,
+        dragging: bool,
+        long_pressed: bool,
+    },
+    Pinching {
+        distance: u32,
+    },
+}
+
+/// Turns the successive touch controller reads into gestures
+pub struct GestureRecognizer {
+    state: RecognizerState,
+    last_tap: Option<(Instant, Position)>,
+    calibration: TouchCalibration,
+    screen: Screen,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        GestureRecognizer {
+            state: RecognizerState::Idle,
+            last_tap: None,
+            calibration: TouchCalibration::default(),
+            screen: Screen::default(),
+        }
+    }
+
+    pub fn set_calibration(&mut self, calibration: TouchCalibration, screen: Screen) {
+        self.calibration = calibration;
+        self.screen = screen;
+    }
+
+    /// No finger is down
+    pub fn is_idle(&self) -> bool {
+        matches!(self.state, RecognizerState::Idle)
+    }
+
+    /// Must be called with every read, empty ones included as they mean every finger was lifted
+    pub fn update(&mut self, touches: &[TouchPoint], now: Instant) -> Vec<Gesture> {
+        let touches: Vec<&TouchPoint> = touches
+            .iter()
+            .filter(|touch| touch.event != Some(TouchEvent::Release))
+            .collect();
+
+        match touches.as_slice() {
+            [] => self.release(now),
+            [touch] => self.hold(self.calibration.position(touch, self.screen), now),
+            [first, second, ..] => self.pinch(first, second),
+        }
+    }
+
+    fn hold(&mut self, position: Position, now: Instant) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+
+        match &mut self.state {
+            RecognizerState::Idle => {
+                self.state = RecognizerState::Pressed {
+                    start: now,
+                    origin: position,
+                    last: position,
+                    dragging: false,
+                    long_pressed: false,
+                };
+            },
+            RecognizerState::Pressed { start, origin, last, dragging, long_pressed } => {
+                if !*dragging && distance(*origin, position) > DRAG_THRESHOLD {
+                    *dragging = true;
+                }
+
+                if *dragging && *last != position {
+                    gestures.push(Gesture::Drag { from: *last, to: position });
+                    *last = position;
+                }
+
+                if !*dragging && !*long_pressed && now.duration_since(*start) >= LONG_PRESS_DURATION {
+                    *long_pressed = true;
+                    gestures.push(Gesture::LongPress(*origin));
+                }
+            },
+            // Wait for every finger to be lifted
+            RecognizerState::Pinching { .. } => {}
+        }
+
+        gestures
+    }
+
+    fn release(&mut self, now: Instant) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+
+        if let RecognizerState::Pressed { start, origin, last, dragging, long_pressed } = self.state {
+            let duration = now.duration_since(start);
+
+            if dragging {
+                if duration <= SWIPE_MAX_DURATION && distance(origin, last) >= SWIPE_MIN_DISTANCE {
+                    gestures.push(Gesture::Swipe { from: origin, direction: swipe_direction(origin, last) });
+                }
+            }
+            else if long_pressed {
+                // Already emitted while holding
+            }
+            else if duration >= LONG_PRESS_DURATION {
+                gestures.push(Gesture::LongPress(origin));
+            }
+            else {
+                // Not held back until the double tap interval is over, which would delay every tap
+                gestures.push(Gesture::Tap(origin));
+
+                match self.last_tap {
+                    Some((tap_time, tap_position)) if start.duration_since(tap_time) <= DOUBLE_TAP_INTERVAL && distance(tap_position, origin) <= DRAG_THRESHOLD => {
+                        gestures.push(Gesture::DoubleTap(origin));
+                        self.last_tap = None;
+                    },
+                    _ => self.last_tap = Some((now, origin)),
+                }
+            }
+        }
+
+        self.state = RecognizerState::Idle;
+
+        gestures
+    }
+
+    fn pinch(&mut self, first: &TouchPoint, second: &TouchPoint) -> Vec<Gesture> {
+        let dx = first.x.abs_diff(second.x) as u32;
+        let dy = first.y.abs_diff(second.y) as u32;
+        let current_distance = ((dx * dx + dy * dy) as f32).sqrt().max(1.0) as u32;
+
+        let center = self.calibration.position(&TouchPoint {
+            x: (first.x + second.x) / 2,
+            y: (first.y + second.y) / 2,
+            id: first.id,
+            event: first.event,
+        }, self.screen);
+
+        match &mut self.state {
+            RecognizerState::Pinching { distance } => {
+                let zoom = if current_distance * 100 >= *distance * PINCH_STEP {
+                    Some(Zoom::In)
+                }
+                else if current_distance * PINCH_STEP <= *distance * 100 {
+                    Some(Zoom::Out)
+                }
+                else {
+                    None
+                };
+
+                match zoom {
+                    Some(zoom) => {
+                        *distance = current_distance;
+                        vec![Gesture::Pinch { center, zoom }]
+                    },
+                    None => vec![]
+                }
+            },
+            _ => {
+                self.state = RecognizerState::Pinching { distance: current_distance };
+                vec![]
+            }
+        }
+    }
+}
+
+fn distance(a: Position, b: Position) -> u16 {
+    a.x.abs_diff(b.x).max(a.y.abs_diff(b.y))
+}
+
+fn swipe_direction(from: Position, to: Position) -> SwipeDirection {
+    let dx = to.x as i32 - from.x as i32;
+    let dy = to.y as i32 - from.y as i32;
+
+    match dx.abs() > dy.abs() {
+        true if dx > 0 => SwipeDirection::Right,
+        true => SwipeDirection::Left,
+        false if dy > 0 => SwipeDirection::Down,
+        false => SwipeDirection::Up,
+    }
+}
@@ -0,0 +1,54 @@
+use crossbeam_channel::Sender;
+use crate::events::AppEvent;
+use crate::events::gestures::Gesture;
+use crate::notifications::Notification;
+use crate::phone::TouchHealth;
+use crate::settings::SettingChange;
+
+/// Everything the main loop wakes up for
+#[derive(Debug)]
+pub enum PhoneEvent {
+    Gesture(Gesture),
+    TouchHealth(TouchHealth),
+    /// Posted by an app, see `EventSender::post`
+    App(Box<dyn AppEvent>),
+    Notify(Notification),
+    SettingChanged(SettingChange),
+    /// The system state may have changed, e.g. the WiFi got connected
+    System,
+}
+
+/// Posts events to the main loop, from any thread
+#[derive(Debug, Clone)]
+pub struct EventSender(Sender<PhoneEvent>);
+
+impl EventSender {
+    pub fn new(sender: Sender<PhoneEvent>) -> Self {
+        EventSender(sender)
+    }
+
+    pub fn send(&self, event: PhoneEvent) -> anyhow::Result<()> {
+        Ok(self.0.send(event)?)
+    }
+
+    /// Handled by every app whose event type it is, even in the background, e.g. once a background job is done
+    pub fn post<E: AppEvent>(&self, event: E) {
+        // The receiver lives as long as the phone
+        self.0.send(PhoneEvent::App(Box::new(event))).ok();
+    }
+
+    /// Posts a notification from any thread, e.g. a service
+    pub fn notify(&self, notification: Notification) {
+        self.0.send(PhoneEvent::Notify(notification)).ok();
+    }
+
+    /// Tells every app, see `Settings::set`
+    pub fn setting_changed(&self, change: SettingChange) {
+        self.0.send(PhoneEvent::SettingChanged(change)).ok();
+    }
+
+    /// Wakes the main loop up to check the system state
+    pub fn wake(&self) {
+        self.0.send(PhoneEvent::System).ok();
+    }
+}
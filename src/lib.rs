@@ -0,0 +1,14 @@
+pub mod phone;
+pub mod ui;
+pub mod events;
+pub mod drivers;
+pub mod state;
+pub mod apps;
+pub mod app_storage;
+pub mod crash;
+pub mod platform;
+pub mod navigation;
+pub mod notifications;
+pub mod runtime;
+pub mod services;
+pub mod settings;
@@ -1,29 +1,38 @@
-pub mod phone;
-pub mod ui;
-pub mod events;
-pub mod drivers;
-pub mod state;
-pub mod apps;
-
+use std::sync::{Arc, Mutex};
+use crossbeam_channel::Receiver;
 use display_interface_spi::SPIInterface;
 use esp_idf_svc::eventloop::{EspSystemEventLoop};
-use esp_idf_svc::fs::fatfs::Fatfs;
 use esp_idf_svc::hal::delay::Ets;
 use esp_idf_svc::hal::gpio::{AnyInputPin, PinDriver};
 use esp_idf_svc::hal::interrupt::InterruptType;
 use esp_idf_svc::hal::modem::{Modem};
 use esp_idf_svc::hal::prelude::{MegaHertz, Peripherals};
-use esp_idf_svc::hal::sd::{SdCardConfiguration, SdCardDriver};
-use esp_idf_svc::hal::sd::spi::SdSpiHostDriver;
+use esp_idf_svc::hal::reset::restart;
 use esp_idf_svc::hal::spi::{Dma, SpiConfig, SpiDeviceDriver, SpiDriver, SpiDriverConfig};
-use esp_idf_svc::io::vfs::MountedFatfs;
 use esp_idf_svc::nvs::{EspDefaultNvsPartition};
-use esp_idf_svc::wifi::{ClientConfiguration, Configuration, EspWifi};
-use ili9341::{DisplaySize240x320, Ili9341, Orientation};
-use log::info;
+use esp_idf_svc::ipv4::IpEvent;
+use esp_idf_svc::wifi::{ClientConfiguration, Configuration, EspWifi, WifiEvent};
+use log::{info, warn};
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions};
 use mousefood::prelude::*;
-use crate::drivers::ft6206::{FT6206};
-use crate::phone::Phone;
+#[cfg(not(feature = "xpt2046"))]
+use esp32_phone_os::drivers::ft6206::{FT6206};
+#[cfg(feature = "xpt2046")]
+use esp32_phone_os::drivers::resistive::ResistiveConfig;
+#[cfg(feature = "xpt2046")]
+use esp32_phone_os::drivers::xpt2046::{XPT2046, XPT2046Spi};
+use esp32_phone_os::crash::{self, CrashRecord};
+use esp32_phone_os::drivers::framebuffer::DirtyFramebuffer;
+use esp32_phone_os::drivers::panel::Panel;
+use esp32_phone_os::drivers::shared_display::SharedDisplay;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::phone::{Detection, Phone};
+use esp32_phone_os::platform::esp::{NvsSettings, SdCardSlot, SntpTimeSource};
+
+const SD_CARD_MOUNT_POINT: &str = "/sdcard";
+#[cfg(feature = "record-touches")]
+const TOUCH_RECORDING_FILE: &str = "touches.jsonl";
 
 // Make sure large allocations go to PSRAM
 #[link_section = ".psram"]
@@ -36,12 +45,20 @@ fn main() -> anyhow::Result<()> {
 
     info!("Hello!");
 
-    let mut phone = Phone::new();
+    let mut phone = Phone::new(Box::new(SntpTimeSource::default()));
 
     let peripherals = Peripherals::take()?;
     let sysloop = EspSystemEventLoop::take()?;
     let nvs_default_partition = EspDefaultNvsPartition::take()?;
 
+    // Defaults in memory if the partition cannot be read
+    match NvsSettings::new(nvs_default_partition.clone()) {
+        Ok(store) => if let Err(error) = phone.phone_data.settings.load(Box::new(store)) {
+            warn!("Could not load the settings: {}", error);
+        },
+        Err(error) => warn!("No settings storage: {}", error),
+    }
+
     /* ===== HSPI ===== */
 
     let spi2 = peripherals.spi2;
@@ -83,70 +100,178 @@ fn main() -> anyhow::Result<()> {
     let vspi_driver_config = SpiDriverConfig::new()
         .dma(Dma::Channel2(4096));
 
-    let vspi_driver = SpiDriver::new(
+    // Used by the SD card for as long as the phone runs
+    let vspi_driver: &'static SpiDriver<'static> = Box::leak(Box::new(SpiDriver::new(
         spi3,
         sclk,
         sdo,
         Some(sdi),
         &vspi_driver_config
-    )?;
-
-    let mut sdcard_configuration = SdCardConfiguration::new();
-    sdcard_configuration.speed_khz = 4_000;
-
-    let sd_card_driver = SdCardDriver::new_spi(
-        SdSpiHostDriver::new(
-            vspi_driver,
-            Some(cs), //None::<AnyOutputPin>,
-            None::<AnyInputPin>,
-            None::<AnyInputPin>,
-            None::<AnyInputPin>,
-            None,
-        )?,
-        &sdcard_configuration,
-    )?;
+    )?));
 
     /* ===== I2C ===== */
 
-    let i2c = peripherals.i2c0;
-    let scl = peripherals.pins.gpio22;
-    let sda_i2c = peripherals.pins.gpio21;
+    #[cfg(not(feature = "xpt2046"))]
+    let (i2c, scl, sda_i2c) = (peripherals.i2c0, peripherals.pins.gpio22, peripherals.pins.gpio21);
 
-    /* ===== Display & touch ===== */
+    // FT6206 INT, e.g. `Some(peripherals.pins.gpio27.into())` on boards wiring it. The board of diagram.json
+    // does not, so the controller is polled.
+    #[cfg(not(feature = "xpt2046"))]
+    let touch_interrupt: Option<AnyInputPin> = None;
 
-    let mut display = Ili9341::new(
-        di,
-        rst,
-        &mut Ets,
-        Orientation::Portrait,
-        DisplaySize240x320,
-    ).unwrap();
+    /* ===== SD Card ===== */
+
+    // Mounted first, as it tells which display the board has. Without a card, the phone boots with the defaults
+    // and mounts it once inserted.
+    phone.storage_slot = Some(Box::new(SdCardSlot::new(vspi_driver, cs.into(), SD_CARD_MOUNT_POINT)));
+    phone.storage_check();
+
+    // Panics are saved to the SD card once `init` loaded it, and shown on the next boot
+    crash::install_panic_hook(&phone);
 
-    let touch_controller = FT6206::new(i2c, sda_i2c, scl)?;
+    /* ===== Display & touch ===== */
+
+    let display_model = phone.load_display_model()?;
+    let mut display = SharedDisplay::new(Panel::new(display_model, di, rst, &mut Ets)?);
+
+    // Without unwinding, panics of this thread are shown by the panic hook. Unless it panicked while drawing,
+    // in which case the phone reboots straight away.
+    let crash_display = display.clone();
+    let crash_events = phone.events.clone();
+    crash::install_crash_screen(move |record| {
+        if let Some(mut display) = crash_display.try_lock() {
+            if let Err(error) = reboot_after_crash(&mut *display, &crash_events, record) {
+                warn!("Could not show the crash: {}", error);
+            }
+        }
+    });
+
+    #[cfg(not(feature = "xpt2046"))]
+    let mut touch_controller = FT6206::new(i2c, sda_i2c, scl, touch_interrupt)?;
+
+    // Wiring of the Cheap Yellow Display boards
+    #[cfg(feature = "xpt2046")]
+    let mut touch_controller = {
+        let xpt2046_spi = XPT2046Spi::new(
+            peripherals.pins.gpio25.into(), // CLK
+            peripherals.pins.gpio32.into(), // MOSI
+            peripherals.pins.gpio39.into(), // MISO
+            peripherals.pins.gpio33.into(), // CS
+        )?;
+
+        let resistive_config = ResistiveConfig {
+            panel: display_model,
+            ..Default::default()
+        };
+
+        // PENIRQ
+        let touch_interrupt = Some(peripherals.pins.gpio36.into());
+
+        XPT2046::new(xpt2046_spi, touch_interrupt, resistive_config)?
+    };
+
+    // Backlight of the Cheap Yellow Display boards, on for as long as the phone runs
+    #[cfg(feature = "xpt2046")]
+    let _backlight = {
+        let mut backlight = PinDriver::output(peripherals.pins.gpio21)?;
+        backlight.set_high()?;
+        backlight
+    };
 
     /* ===== TUI ===== */
 
-    let backend = EmbeddedBackend::new(&mut display, EmbeddedBackendConfig::default());
-    let mut terminal = Terminal::new(backend)?;
+    // Splash screen, the backend borrows the display until the end of the block
+    {
+        let mut framebuffer = DirtyFramebuffer::new(&mut display);
+        let backend = EmbeddedBackend::new(&mut framebuffer, framebuffer_config());
+        let mut terminal = Terminal::new(backend)?;
 
-    terminal.draw(|frame| phone.render_homepage(frame))?;
+        terminal.draw(|frame| phone.render_homepage(frame))?;
+    }
+
+    // Holding a touch on the splash screen starts only the core apps
+    phone.detect_safe_mode(&mut touch_controller);
 
     /* ===== WiFi ===== */
 
-    let wifi = init_wifi(peripherals.modem, sysloop, nvs_default_partition)?;
+    // Connections and disconnections wake the main loop up, subscriptions last as long as the phone
+    let events = phone.phone_data.events.clone();
+    let _wifi_subscription = sysloop.subscribe::<WifiEvent, _>(move |_| events.wake())?;
+    let events = phone.phone_data.events.clone();
+    let _ip_subscription = sysloop.subscribe::<IpEvent, _>(move |_| events.wake())?;
+
+    // Offline without it
+    match init_wifi(peripherals.modem, sysloop.clone(), nvs_default_partition) {
+        Ok(wifi) => {
+            phone.phone_data.wifi = Some(Arc::new(Mutex::new(wifi)));
+            phone.phone_data.wifi_hardware = Detection::Detected;
+        },
+        Err(error) => {
+            warn!("No WiFi: {}", error);
+            phone.phone_data.wifi_hardware = Detection::Missing;
+        },
+    }
+
+    /* ===== Touch recording ===== */
+
+    // Boots without recording if the SD card is missing
+    #[cfg(feature = "record-touches")]
+    let touch_controller = esp32_phone_os::platform::recording::TouchRecorder::new(
+        touch_controller,
+        &std::path::Path::new(SD_CARD_MOUNT_POINT).join(TOUCH_RECORDING_FILE),
+    );
 
-    phone.phone_data.wifi = Some(wifi);
+    /* ===== Main loop ===== */
 
-    /* ===== SD Card ===== */
+    phone.spawn_touch_thread(touch_controller);
 
-    phone.fs = Some(MountedFatfs::mount(Fatfs::new_sdcard(0, sd_card_driver)?, "/sdcard", 10)?);
+    if let Some(record) = crash::catch(|| phone.init()) {
+        reboot_after_crash(&mut display, &phone.events, &record)?;
+    }
 
-    /* ===== Main loop ===== */
+    // Each rotation rebuilds the terminal, as the backend computes its cell grid once
+    loop {
+        display.lock().set_orientation(phone.phone_data.screen.orientation)?;
+
+        let mut framebuffer = DirtyFramebuffer::new(&mut display);
+        let backend = EmbeddedBackend::new(&mut framebuffer, framebuffer_config());
+        let terminal = Terminal::new(backend)?;
 
-    phone.init()?;
-    phone.event_loop(terminal, touch_controller)?;
+        // Instead of freezing on the last frame
+        if let Some(record) = crash::catch(|| phone.event_loop(terminal)) {
+            reboot_after_crash(&mut display, &phone.events, &record)?;
+        }
+    }
+}
+
+/// Shows the crash screen until the user asks for a reboot, straight to the display so that nothing is allocated after a crash
+fn reboot_after_crash<D>(display: &mut D, events: &Receiver<PhoneEvent>, record: &CrashRecord) -> anyhow::Result<()>
+where
+    D: DrawTarget<Color = Rgb565> + OriginDimensions,
+    D::Error: std::fmt::Debug,
+{
+    let backend = EmbeddedBackend::new(display, EmbeddedBackendConfig::default());
+    let mut terminal = Terminal::new(backend)?;
+
+    crash::wait_for_reboot(&mut terminal, events, record, crash::CRASH_REBOOT_TIMEOUT)?;
+
+    restart()
+}
 
-    Ok(())
+/// Pushes the cells changed by each frame to the display, the backend handing the whole screen over on each flush
+fn framebuffer_config<'display, D>() -> EmbeddedBackendConfig<DirtyFramebuffer<'display, D>, Rgb565>
+where
+    D: DrawTarget<Color = Rgb565> + OriginDimensions,
+    D::Error: std::fmt::Debug,
+{
+    EmbeddedBackendConfig {
+        flush_callback: Box::new(|framebuffer| {
+            if let Err(error) = framebuffer.flush() {
+                warn!("Could not flush the display: {:?}", error);
+            }
+        }),
+        ..Default::default()
+    }
 }
 
 fn init_wifi(modem: Modem, sysloop: EspSystemEventLoop, nvs_default_partition: EspDefaultNvsPartition) -> anyhow::Result<EspWifi<'static>> {
@@ -0,0 +1,190 @@
+use anyhow::anyhow;
+use log::{info, warn};
+use crate::events::AppEvent;
+use crate::phone::{Phone, PhoneData};
+use crate::runtime::block_on;
+use crate::state::PhoneState;
+use crate::ui::widgets::dialog::{Dialog, DialogEvent};
+
+/// Leads back from a screen, see `Phone::back`
+#[derive(Debug)]
+pub enum Route {
+    /// Leaves the app, to the app below or the homepage
+    App(usize),
+    /// Stays in the app, which handles the event to display its previous screen
+    Screen(usize, Box<dyn AppEvent>),
+}
+
+impl Route {
+    pub fn app(&self) -> usize {
+        match self {
+            Route::App(index) | Route::Screen(index, _) => *index,
+        }
+    }
+}
+
+/// Opens an app at a given screen, e.g. the WiFi password screen of an access point
+#[derive(Debug)]
+pub struct DeepLink {
+    /// `App::app_name`
+    pub app: &'static str,
+    /// Handled by the app once in the foreground
+    pub event: Box<dyn AppEvent>,
+}
+
+impl DeepLink {
+    pub fn new<E: AppEvent>(app: &'static str, event: E) -> Self {
+        DeepLink {
+            app,
+            event: Box::new(event),
+        }
+    }
+}
+
+impl PhoneData {
+    /// Called by the foreground app moving to another of its screens, `back` is the event leading back to the current one
+    pub fn push_screen<E: AppEvent>(&mut self, back: E) {
+        self.pushed_screens.push(Box::new(back));
+    }
+}
+
+impl Phone {
+    /// Switches the foreground, calling the lifecycle hooks of the apps involved.
+    /// An app already on the back stack is returned to, leaving the apps above it.
+    pub fn set_state(&mut self, state: PhoneState) -> anyhow::Result<()> {
+        if state == self.state {
+            return Ok(());
+        }
+
+        self.shade_open = false;
+
+        // Stays where it is, telling why the app cannot be opened
+        if let PhoneState::InApp(index) = state {
+            let app_name = self.apps[index].app_name();
+
+            if let Some(reason) = self.disabled_apps.get(app_name) {
+                let title = format!("{} could not start", app_name);
+                self.phone_data.show_dialog(Dialog::alert(&title, reason));
+                return Ok(());
+            }
+        }
+
+        match state {
+            PhoneState::Homepage => self.unwind_to(None)?,
+            PhoneState::InApp(index) => match self.navigation.iter().any(|route| route.app() == index) {
+                true => self.unwind_to(Some(index))?,
+                false => {
+                    if let PhoneState::InApp(current) = self.state {
+                        block_on(self.apps[current].on_pause(&mut self.phone_data))?;
+                    }
+
+                    self.navigation.push(Route::App(index));
+                },
+            },
+        }
+
+        self.state = state;
+
+        if let PhoneState::InApp(index) = state {
+            block_on(self.apps[index].on_enter(&mut self.phone_data))?;
+            self.collect_screens(index);
+        }
+
+        Ok(())
+    }
+
+    /// Pops the back stack until the app is on top, the homepage for `None`
+    fn unwind_to(&mut self, app: Option<usize>) -> anyhow::Result<()> {
+        while let Some(route) = self.navigation.last() {
+            if Some(route.app()) == app {
+                break;
+            }
+
+            if let Some(Route::App(index)) = self.navigation.pop() {
+                block_on(self.apps[index].on_exit(&mut self.phone_data))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// System-wide back action, from the state bar or a swipe from the left edge
+    pub fn back(&mut self) -> anyhow::Result<()> {
+        // A dialog covers everything, an alert without cancel button is simply acknowledged
+        if let Some(dialog) = self.phone_data.dialogs.first() {
+            let event = match dialog.cancel_label {
+                Some(_) => DialogEvent::Cancel,
+                None => DialogEvent::Confirm,
+            };
+
+            if let Some(state) = self.handle_dialog_event(&event)? {
+                self.set_state(state)?;
+            }
+
+            return Ok(());
+        }
+
+        // The shade covers the screen, so it goes first
+        if self.shade_open {
+            self.shade_open = false;
+            return Ok(());
+        }
+
+        match self.navigation.pop() {
+            None => info!("Already on the homepage"),
+            Some(Route::Screen(index, event)) => {
+                let state = block_on(self.apps[index].handle_event(&mut self.phone_data, event.as_ref()))?;
+                self.collect_screens(index);
+
+                if let Some(state) = state {
+                    self.set_state(state)?;
+                }
+            },
+            Some(Route::App(index)) => {
+                block_on(self.apps[index].on_exit(&mut self.phone_data))?;
+
+                self.state = match self.navigation.last() {
+                    None => PhoneState::Homepage,
+                    Some(route) => PhoneState::InApp(route.app()),
+                };
+
+                if let PhoneState::InApp(previous) = self.state {
+                    block_on(self.apps[previous].on_enter(&mut self.phone_data))?;
+                    self.collect_screens(previous);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn open(&mut self, link: &DeepLink) -> anyhow::Result<Option<PhoneState>> {
+        let index = self.apps
+            .iter()
+            .position(|app| app.app_name() == link.app)
+            .ok_or_else(|| anyhow!("No app named {}", link.app))?;
+
+        self.set_state(PhoneState::InApp(index))?;
+
+        if self.state != PhoneState::InApp(index) {
+            return Ok(None);
+        }
+
+        let state = block_on(self.apps[index].handle_event(&mut self.phone_data, link.event.as_ref()))?;
+        self.collect_screens(index);
+
+        Ok(state)
+    }
+
+    /// Moves the screens pushed by the app to the back stack, only the foreground app may navigate
+    pub(crate) fn collect_screens(&mut self, index: usize) {
+        let screens = std::mem::take(&mut self.phone_data.pushed_screens);
+
+        match self.state == PhoneState::InApp(index) {
+            true => self.navigation.extend(screens.into_iter().map(|back| Route::Screen(index, back))),
+            false => if !screens.is_empty() {
+                warn!("{} is in the background, its screens are dropped", self.apps[index].app_name());
+            },
+        }
+    }
+}
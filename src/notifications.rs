@@ -0,0 +1,227 @@
+use std::path::Path;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::events::AppEvent;
+use crate::events::gestures::Gesture;
+use crate::phone::{Phone, PhoneData};
+use crate::platform::Storage;
+use crate::state::PhoneState;
+use crate::ui::widgets::scrollable_list::ScrollableList;
+
+pub const NOTIFICATIONS_PATH: &str = "notifications.json";
+/// The oldest notifications are dropped beyond it
+pub const MAX_NOTIFICATIONS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    /// Listed in the shade only
+    Low,
+    Normal,
+    /// Turns the unread indicator red
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Notification {
+    /// Assigned when posted
+    pub id: u32,
+    /// `App::app_name` of the sender
+    pub app: String,
+    pub title: String,
+    pub body: String,
+    pub priority: Priority,
+    pub read: bool,
+    /// Seconds since the UNIX epoch, assigned when posted
+    pub time: u64,
+    /// Handled when the notification is tapped, e.g. a `CoreEvent::Open`.
+    /// Events cannot be saved, so it does not survive a reboot.
+    #[serde(skip)]
+    pub action: Option<Box<dyn AppEvent>>,
+}
+
+impl Notification {
+    pub fn new(app: &str, title: &str, body: &str, priority: Priority) -> Self {
+        Notification {
+            id: 0,
+            app: String::from(app),
+            title: String::from(title),
+            body: String::from(body),
+            priority,
+            read: false,
+            time: 0,
+            action: None,
+        }
+    }
+
+    pub fn with_action<E: AppEvent>(mut self, action: E) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+}
+
+/// Shade actions, see `CoreEvent::Notification`
+#[derive(Debug)]
+pub enum NotificationEvent {
+    OpenShade,
+    CloseShade,
+    Dismiss(u32),
+    /// Dismisses the notification and handles its action
+    Act(u32),
+    Clear,
+    /// Dragging over the shade, see `Phone::shade_list`
+    Scroll(Gesture),
+}
+
+/// Posted notifications, saved to the storage on every change
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NotificationCenter {
+    notifications: Vec<Notification>,
+    next_id: u32,
+    /// The notifications from this id on were posted without a card, see `merge`
+    #[serde(skip)]
+    unsaved_from: u32,
+}
+
+impl NotificationCenter {
+    pub fn load(storage: &dyn Storage) -> anyhow::Result<Option<Self>> {
+        match storage.read_to_string(Path::new(NOTIFICATIONS_PATH))? {
+            Some(content) => {
+                info!("Notifications loaded");
+                Ok(Some(serde_json::from_str(&content)?))
+            },
+            None => Ok(None)
+        }
+    }
+
+    pub fn save(&self, storage: &dyn Storage) -> anyhow::Result<()> {
+        storage.write(Path::new(NOTIFICATIONS_PATH), serde_json::to_string(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Called once the card is removed, its file already holds the notifications posted until then
+    pub fn mark_saved(&mut self) {
+        self.unsaved_from = self.next_id;
+    }
+
+    /// Posts the notifications the other center received without a card after these ones, keeping their time
+    pub fn merge(&mut self, other: NotificationCenter) {
+        for notification in other.notifications.into_iter().filter(|notification| notification.id >= other.unsaved_from) {
+            let time = notification.time;
+            self.post(notification, time);
+        }
+    }
+
+    pub fn post(&mut self, mut notification: Notification, time: u64) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        notification.id = id;
+        notification.time = time;
+
+        self.notifications.push(notification);
+
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications.remove(0);
+        }
+
+        id
+    }
+
+    /// Removes the notification, returning it for its action
+    pub fn dismiss(&mut self, id: u32) -> Option<Notification> {
+        let index = self.notifications.iter().position(|notification| notification.id == id)?;
+        Some(self.notifications.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+    }
+
+    pub fn mark_all_read(&mut self) {
+        for notification in &mut self.notifications {
+            notification.read = true;
+        }
+    }
+
+    /// Unread notifications worth the indicator, with their highest priority
+    pub fn unread(&self) -> Option<(usize, Priority)> {
+        let unread: Vec<Priority> = self.notifications
+            .iter()
+            .filter(|notification| !notification.read && notification.priority > Priority::Low)
+            .map(|notification| notification.priority)
+            .collect();
+
+        Some((unread.len(), unread.iter().max().copied()?))
+    }
+
+    /// Most important first, then newest first
+    pub fn sorted(&self) -> Vec<&Notification> {
+        let mut notifications: Vec<&Notification> = self.notifications.iter().collect();
+        notifications.sort_by(|a, b| b.priority.cmp(&a.priority).then(b.id.cmp(&a.id)));
+        notifications
+    }
+
+    pub fn len(&self) -> usize {
+        self.notifications.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifications.is_empty()
+    }
+}
+
+impl PhoneData {
+    /// Posts a notification from the UI thread, e.g. from `handle_event`, other threads use `EventSender::notify`
+    pub fn notify(&mut self, notification: Notification) -> u32 {
+        let id = self.notifications.post(notification, self.time.now().as_secs());
+        self.save_notifications();
+        self.redraw.mark_dirty();
+        id
+    }
+
+    pub fn save_notifications(&self) {
+        let Some(storage) = self.storage.as_deref() else {
+            return;
+        };
+
+        // Losing notifications is not worth stopping the phone
+        if let Err(error) = self.notifications.save(storage) {
+            warn!("Could not save the notifications: {}", error);
+        }
+    }
+}
+
+impl Phone {
+    pub fn handle_notification_event(&mut self, event: &NotificationEvent) -> anyhow::Result<Option<PhoneState>> {
+        match event {
+            NotificationEvent::OpenShade => {
+                self.shade_open = true;
+                // From the top, the most important first
+                self.shade_list = ScrollableList::default();
+                self.phone_data.notifications.mark_all_read();
+                self.phone_data.save_notifications();
+            },
+            NotificationEvent::CloseShade => self.shade_open = false,
+            NotificationEvent::Dismiss(id) => {
+                self.phone_data.notifications.dismiss(*id);
+                self.phone_data.save_notifications();
+            },
+            NotificationEvent::Act(id) => {
+                let notification = self.phone_data.notifications.dismiss(*id);
+                self.phone_data.save_notifications();
+                self.shade_open = false;
+
+                if let Some(action) = notification.and_then(|notification| notification.action) {
+                    return self.handle_posted_event(action.as_ref());
+                }
+            },
+            NotificationEvent::Clear => {
+                self.phone_data.notifications.clear();
+                self.phone_data.save_notifications();
+            },
+            NotificationEvent::Scroll(gesture) => self.shade_list.handle_gesture(gesture),
+        }
+
+        Ok(None)
+    }
+}
@@ -1,143 +1,704 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::thread::sleep;
-use std::time::Duration;
-use crossbeam_channel::bounded;
-use esp_idf_svc::fs::fatfs::{Fatfs};
-use esp_idf_svc::hal::sd::SdCardDriver;
-use esp_idf_svc::hal::sd::spi::SdSpiHostDriver;
-use esp_idf_svc::hal::spi::SpiDriver;
-use esp_idf_svc::io::vfs::MountedFatfs;
-use esp_idf_svc::sntp::{EspSntp, SyncStatus};
+use std::time::{Duration, Instant};
+use anyhow::anyhow;
+use crossbeam_channel::{bounded, unbounded, Receiver, RecvTimeoutError, TryRecvError};
 use crate::apps::app::{App, AppImpl};
+use crate::apps::calibration::CalibrationApp;
+use crate::apps::display::DisplayApp;
+use crate::apps::storage::StorageApp;
 use crate::apps::wifi::WifiApp;
-use crate::events::EventType;
-use crate::drivers::ft6206::FT6206;
+use crate::app_storage::AppUsage;
+use crate::crash::{self, CrashStorageRoot};
+use crate::drivers::display::{DisplayModel, Screen};
+use crate::events::{AppEvent, CoreEvent, EventType};
+use crate::events::calibration::{TouchCalibration, TouchContext};
+use crate::events::gestures::GestureRecognizer;
+use crate::events::queue::{EventSender, PhoneEvent};
+use crate::navigation::Route;
+use crate::notifications::NotificationCenter;
+use crate::platform::{free_memory, SharedWifi, Storage, StorageSlot, TimeSource, TouchInput};
+use crate::platform::recording::TouchRecord;
+use crate::platform::supervisor::{TouchConfig, TouchSupervisor};
+use crate::runtime::{block_on, unblock, Runtime};
+use crate::services::Services;
+use crate::settings::Settings;
 use crate::state::PhoneState;
+use crate::ui::redraw::RedrawScheduler;
+use crate::ui::widgets::dialog::Dialog;
 use crate::ui::widgets::keyboard::Keyboard;
-use esp_idf_svc::wifi::EspWifi;
-use log::{info};
+use crate::ui::widgets::scrollable_list::ScrollableList;
+use crate::ui::widgets::toast::Toast;
+use log::{info, warn};
 use mousefood::prelude::{Backend, Frame, Terminal};
 
-pub struct Phone<'a> {
+const MAX_REPLAY_STEPS: usize = 100;
+/// Background apps are suspended below this much free heap
+const LOW_MEMORY_THRESHOLD: usize = 32 * 1024;
+/// Touch reads spanning half a second
+const SAFE_MODE_SAMPLES: usize = 5;
+const SAFE_MODE_SAMPLE_PERIOD: Duration = Duration::from_millis(100);
+/// Between two attempts to mount the SD card while there is none
+const STORAGE_RETRY_PERIOD: Duration = Duration::from_secs(5);
+/// Between two calls of `Phone::main_thread_hook`
+pub const MAIN_THREAD_HOOK_PERIOD: Duration = Duration::from_millis(16);
+
+pub struct Phone {
     pub state: PhoneState,
     pub should_wait_touch: bool,
     pub phone_data: PhoneData,
     pub apps: Vec<Box<dyn App + 'static>>,
-    pub fs: Option<MountedFatfs<Fatfs<SdCardDriver<SdSpiHostDriver<'a, SpiDriver<'a>>>>>>
+    pub app_list: ScrollableList,
+    pub touch_config: TouchConfig,
+    /// Touch, app and system events, in the order they happened
+    pub events: Receiver<PhoneEvent>,
+    /// The notification shade covers the screen, see `Phone::render_shade`
+    pub shade_open: bool,
+    pub shade_list: ScrollableList,
+    /// Back stack, the last route leads back from the current screen, empty on the homepage
+    pub navigation: Vec<Route>,
+    /// Background apps are suspended once per low memory episode
+    memory_low: bool,
+    /// Only the core apps are started, see `Phone::detect_safe_mode`
+    pub safe_mode: bool,
+    /// Apps which could not start, by `App::app_name`, with the reason shown when opened
+    pub disabled_apps: HashMap<&'static str, String>,
+    /// Mounted into `PhoneData::storage` as soon as a card is found
+    pub storage_slot: Option<Box<dyn StorageSlot>>,
+    /// Mount attempts and removal checks are spaced by `STORAGE_RETRY_PERIOD`, `None` tries again at the next step
+    pub storage_attempt: Option<Instant>,
+    /// Answer of the unblock worker running the last attempt or check
+    storage_probe: Option<Receiver<StorageProbe>>,
+    /// Where the panic hook saves the crashes, follows `PhoneData::storage`
+    pub crash_storage_root: CrashStorageRoot,
+    /// Run by `Phone::event_loop` every `MAIN_THREAD_HOOK_PERIOD`, e.g. for the simulator window to handle its events
+    pub main_thread_hook: Option<Box<dyn FnMut()>>,
 }
 
 pub struct PhoneData {
-    pub wifi: Option<EspWifi<'static>>,
+    pub wifi: Option<SharedWifi>,
     pub wifi_state: WifiState,
-    pub ntp: Option<EspSntp<'static>>,
-    pub keyboard: Option<Keyboard>
+    pub touch_health: TouchHealth,
+    pub touch: Arc<Mutex<TouchContext>>,
+    /// Changing it rebuilds the terminal, see `Phone::event_loop`
+    pub screen: Screen,
+    pub time: Box<dyn TimeSource>,
+    pub storage: Option<Box<dyn Storage>>,
+    /// The phone boots without it, storing nothing
+    pub sd_card: Detection,
+    /// The phone boots without it, offline
+    pub wifi_hardware: Detection,
+    pub keyboard: Option<Keyboard>,
+    pub events: EventSender,
+    pub runtime: Runtime,
+    /// Screens the foreground app navigated to, see `PhoneData::push_screen`
+    pub pushed_screens: Vec<Box<dyn AppEvent>>,
+    pub notifications: NotificationCenter,
+    pub settings: Settings,
+    /// Shared with the app storages, see `PhoneData::app_storage`
+    pub app_usage: AppUsage,
+    pub toast: Option<Toast>,
+    /// Shown one after the other, see `PhoneData::show_dialog`
+    pub dialogs: Vec<Dialog>,
+    /// Background services of the apps, keyed by app name
+    pub services: Services,
+    /// Events mark the screen dirty already, apps only need it for changes happening on their own
+    pub redraw: RedrawScheduler,
+}
+
+/// Answer of an unblock worker, see `Phone::storage_check`
+enum StorageProbe {
+    /// The slot comes back with the card it mounted
+    Mounted(Box<dyn StorageSlot>, anyhow::Result<Box<dyn Storage + Send>>),
+    /// Whether the mounted card can still be read
+    Readable(bool),
+}
+
+/// Optional hardware, probed during boot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Detection {
+    /// Not probed, e.g. in the host tests
+    Unknown,
+    Detected,
+    Missing,
 }
 
 #[derive(PartialEq)]
 pub enum WifiState {
+    /// No WiFi hardware
+    Unavailable,
     NotInitialized,
     NotConnected,
     Connecting,
     Connected(String),
 }
 
-impl Phone<'_> {
-    pub fn new() -> Self {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchHealth {
+    Healthy,
+    /// Reads are failing and being retried
+    Degraded,
+    /// The controller could not be re-initialised
+    Unavailable,
+}
+
+impl Phone {
+    pub fn new(time: Box<dyn TimeSource>) -> Self {
+        let (event_sender, event_receiver) = unbounded();
+        let events = EventSender::new(event_sender);
+        let runtime = Runtime::new();
+
         Phone {
             state: PhoneState::Homepage,
             should_wait_touch: true,
             phone_data: PhoneData {
                 wifi: None,
                 wifi_state: WifiState::NotInitialized,
-                ntp: None,
+                touch_health: TouchHealth::Healthy,
+                touch: Arc::new(Mutex::new(TouchContext::default())),
+                screen: Screen::default(),
+                time,
+                storage: None,
+                sd_card: Detection::Unknown,
+                wifi_hardware: Detection::Unknown,
                 keyboard: None,
+                pushed_screens: vec![],
+                notifications: NotificationCenter::default(),
+                settings: Settings::new(events.clone()),
+                app_usage: AppUsage::default(),
+                toast: None,
+                dialogs: vec![],
+                services: Services::new(events.clone()),
+                events,
+                runtime,
+                redraw: RedrawScheduler::default(),
             },
             apps: vec![
-                AppImpl::<WifiApp>::new_boxed()
+                AppImpl::<WifiApp>::new_boxed(),
+                AppImpl::<CalibrationApp>::new_boxed(),
+                AppImpl::<DisplayApp>::new_boxed(),
+                AppImpl::<StorageApp>::new_boxed(),
             ],
-            fs: None,
+            app_list: ScrollableList::default(),
+            touch_config: TouchConfig::default(),
+            events: event_receiver,
+            shade_open: false,
+            shade_list: ScrollableList::default(),
+            navigation: vec![],
+            memory_low: false,
+            safe_mode: false,
+            disabled_apps: HashMap::new(),
+            storage_slot: None,
+            storage_attempt: None,
+            storage_probe: None,
+            crash_storage_root: CrashStorageRoot::default(),
+            main_thread_hook: None,
         }
     }
 
     pub fn init(&mut self) -> anyhow::Result<()> {
+        self.load_storage()?;
+
+        // A failing app is disabled, the others still start
         for app in self.apps.iter_mut() {
-            app.init(&mut self.phone_data)?;
+            let result = match self.safe_mode && !app.is_core() {
+                true => Err(anyhow!("Not started in safe mode")),
+                false => block_on(app.init(&mut self.phone_data)),
+            };
+
+            if let Err(error) = result {
+                warn!("{} disabled: {}", app.app_name(), error);
+                self.disabled_apps.insert(app.app_name(), error.to_string());
+                self.phone_data.services.stop_app(app.app_name());
+            }
         }
 
         Ok(())
     }
 
-    pub fn event_loop<B: Backend>(&mut self, mut terminal: Terminal<B>, mut touch_controller: FT6206) -> anyhow::Result<()> {
-        let (touch_sender, touch_receiver) = bounded(3);
+    /// What the phone keeps on the SD card, defaults without it
+    fn load_storage(&mut self) -> anyhow::Result<()> {
+        *self.crash_storage_root.lock().unwrap() = self.phone_data.storage.as_deref().map(|storage| storage.root().to_path_buf());
+
+        let calibration = match self.phone_data.storage.as_deref() {
+            Some(storage) => TouchCalibration::load(storage)?,
+            None => None,
+        };
+
+        self.phone_data.touch.lock().unwrap().calibration = calibration.unwrap_or_else(|| TouchCalibration::for_model(self.phone_data.screen.model));
+
+        if let Err(error) = self.create_app_directories() {
+            warn!("Could not create the app directories: {}", error);
+        }
+
+        if let Some(storage) = self.phone_data.storage.as_deref() {
+            // A corrupted file only costs the notifications
+            match NotificationCenter::load(storage) {
+                Ok(notifications) => {
+                    // Received while there was no card
+                    let received = std::mem::replace(&mut self.phone_data.notifications, notifications.unwrap_or_default());
+                    self.phone_data.notifications.merge(received);
+                },
+                Err(error) => warn!("Could not load the notifications: {}", error),
+            }
+        }
+
+        if let Err(error) = self.report_last_crash() {
+            warn!("Could not read the last crash: {}", error);
+        }
+
+        Ok(())
+    }
+
+    /// Mounts the SD card once inserted, then reloads what the phone and its apps keep on it, and forgets it once removed
+    ///
+    /// Only the first attempt, during boot, blocks the UI thread, the others run on the unblock workers
+    pub fn storage_check(&mut self) {
+        if let Some(probe) = &self.storage_probe {
+            match probe.try_recv() {
+                Ok(StorageProbe::Mounted(storage_slot, storage)) => {
+                    self.storage_slot = Some(storage_slot);
+                    self.storage_mounted(storage);
+                },
+                Ok(StorageProbe::Readable(readable)) => {
+                    if !readable {
+                        self.storage_removed();
+                    }
+                },
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => warn!("SD card check failed, the slot is lost"),
+            }
+
+            self.storage_probe = None;
+            return;
+        }
+
+        if self.storage_slot.is_none() || self.storage_attempt.is_some_and(|attempt| attempt.elapsed() < STORAGE_RETRY_PERIOD) {
+            return;
+        }
+
+        self.storage_attempt = Some(Instant::now());
+
+        // `init` needs the card during boot
+        if self.phone_data.sd_card == Detection::Unknown {
+            if let Some(storage_slot) = self.storage_slot.as_mut() {
+                let storage = storage_slot.mount();
+                self.storage_mounted(storage);
+            }
+
+            return;
+        }
+
+        let (sender, receiver) = bounded(1);
+        let events = self.phone_data.events.clone();
+
+        match self.phone_data.storage.as_deref() {
+            // A removed card is noticed once its root cannot be listed anymore
+            Some(storage) => {
+                let root = storage.root().to_path_buf();
+
+                self.phone_data.runtime.spawn(async move {
+                    let readable = unblock(move || fs::read_dir(root).is_ok()).await.unwrap_or(true);
+                    sender.send(StorageProbe::Readable(readable)).ok();
+                    events.wake();
+                });
+            },
+            None => {
+                let Some(mut storage_slot) = self.storage_slot.take() else {
+                    return;
+                };
+
+                self.phone_data.runtime.spawn(async move {
+                    if let Ok(probe) = unblock(move || {
+                        let storage = storage_slot.mount();
+                        StorageProbe::Mounted(storage_slot, storage)
+                    }).await {
+                        sender.send(probe).ok();
+                    }
+
+                    events.wake();
+                });
+            },
+        }
+
+        self.storage_probe = Some(receiver);
+    }
+
+    fn storage_mounted(&mut self, storage: anyhow::Result<Box<dyn Storage + Send>>) {
+        let storage = match storage {
+            Ok(storage) => storage,
+            Err(error) => {
+                if self.phone_data.sd_card != Detection::Missing {
+                    warn!("No SD card: {}", error);
+                    self.phone_data.sd_card = Detection::Missing;
+                    self.phone_data.redraw.mark_dirty();
+                }
+
+                return;
+            },
+        };
+
+        let inserted = self.phone_data.sd_card == Detection::Missing;
+
+        info!("SD card mounted");
+        self.phone_data.storage = Some(storage);
+        self.phone_data.sd_card = Detection::Detected;
+        self.phone_data.redraw.mark_dirty();
+
+        // Loaded by `init` during boot
+        if !inserted {
+            return;
+        }
+
+        if let Err(error) = self.load_storage() {
+            warn!("Could not load the SD card: {}", error);
+        }
+
+        for index in 0..self.apps.len() {
+            if self.is_disabled(index) {
+                continue;
+            }
+
+            if let Err(error) = block_on(self.apps[index].on_storage_mounted(&mut self.phone_data)) {
+                warn!("{} could not load the SD card: {}", self.apps[index].app_name(), error);
+            }
+        }
+
+        self.phone_data.show_toast(Toast::info("SD card inserted"));
+    }
+
+    /// The apps keep what they loaded, their writes fail until the card is inserted again
+    fn storage_removed(&mut self) {
+        warn!("SD card removed");
+
+        self.phone_data.storage = None;
+        self.phone_data.sd_card = Detection::Missing;
+        self.phone_data.app_usage.lock().unwrap().clear();
+        self.phone_data.notifications.mark_saved();
+        *self.crash_storage_root.lock().unwrap() = None;
+
+        self.phone_data.redraw.mark_dirty();
+        self.phone_data.show_toast(Toast::error("SD card removed"));
+    }
+
+    pub fn is_disabled(&self, index: usize) -> bool {
+        self.disabled_apps.contains_key(self.apps[index].app_name())
+    }
+
+    /// Holding a touch for a moment during boot, called before `spawn_touch_thread`
+    pub fn detect_safe_mode<T: TouchInput>(&mut self, touch_controller: &mut T) {
+        for _ in 0..SAFE_MODE_SAMPLES {
+            let touched = touch_controller.read_touches().is_ok_and(|touches| !touches.is_empty());
+
+            if !touched {
+                return;
+            }
+
+            thread::sleep(SAFE_MODE_SAMPLE_PERIOD);
+        }
+
+        info!("Touch held during boot, starting in safe mode");
+        self.safe_mode = true;
+    }
+
+    /// Gestures and health changes are sent to the event queue
+    pub fn spawn_touch_thread<T: TouchInput + 'static>(&self, touch_controller: T) {
+        let events = self.phone_data.events.clone();
+        let touch_config = self.touch_config;
+        let touch_context = self.phone_data.touch.clone();
 
         thread::spawn(move || {
+            let mut touch_controller = TouchSupervisor::new(touch_controller, touch_config);
+            let mut gesture_recognizer = GestureRecognizer::new();
+            let mut health = touch_controller.health();
+            let mut touching = false;
+
             loop {
-                sleep(Duration::from_millis(20));
+                touch_controller.wait(touching);
+
+                let touches = touch_controller.read_touches();
+                touching = !touches.is_empty();
+
+                if touch_controller.health() != health {
+                    health = touch_controller.health();
 
-                let touches = touch_controller.read_touches().unwrap();
+                    if events.send(PhoneEvent::TouchHealth(health)).is_err() {
+                        return;
+                    }
+                }
+
+                let gestures = touch_context.lock().unwrap().recognize(&mut gesture_recognizer, &touches, Instant::now());
 
-                if !touches.is_empty() {
-                    touch_sender.send(touches).unwrap();
-                    sleep(Duration::from_millis(150));
+                for gesture in gestures {
+                    if events.send(PhoneEvent::Gesture(gesture)).is_err() {
+                        return;
+                    }
                 }
             }
         });
+    }
+
+    /// Panel model from the storage, to be called before the display is initialized
+    pub fn load_display_model(&mut self) -> anyhow::Result<DisplayModel> {
+        if let Some(storage) = self.phone_data.storage.as_deref() {
+            if let Some(model) = DisplayModel::load(storage)? {
+                self.phone_data.screen.model = model;
+            }
+        }
+
+        Ok(self.phone_data.screen.model)
+    }
 
+    /// Runs the phone until the orientation changes, the terminal must then be rebuilt for the rotated display
+    pub fn event_loop<B: Backend>(&mut self, mut terminal: Terminal<B>) -> anyhow::Result<()> {
+        let orientation = self.phone_data.screen.orientation;
         let mut current_events = None;
 
+        self.redraw(&mut terminal, &mut current_events)?;
+
+        while self.phone_data.screen.orientation == orientation {
+            let mut timeout = self.next_timeout();
+
+            if let Some(hook) = self.main_thread_hook.as_mut() {
+                hook();
+                timeout = timeout.min(MAIN_THREAD_HOOK_PERIOD);
+            }
+
+            // Sleeps until something happens, or the screen needs a refresh
+            let event = match self.events.recv_timeout(timeout) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return Err(anyhow!("Event queue closed")),
+            };
+
+            self.step(&mut terminal, &mut current_events, event)?;
+
+            // The record is taken by `crash::catch`
+            if crash::panicked() {
+                return Err(anyhow!("A background thread panicked"));
+            }
+        }
+
+        info!("Screen rotated to {:?}", self.phone_data.screen.orientation);
+
+        Ok(())
+    }
+
+    fn until_next_minute(&self) -> Duration {
+        Duration::from_secs(60 - self.phone_data.time.now().as_secs() % 60)
+    }
+
+    /// The clock refresh, the toast going away, or the next check of the SD card
+    fn next_timeout(&self) -> Duration {
+        let mut timeout = self.until_next_minute();
+
+        if let Some(toast) = &self.phone_data.toast {
+            timeout = timeout.min(toast.expires.saturating_duration_since(Instant::now()));
+        }
+
+        if let (None, Some(_), Some(attempt)) = (&self.storage_probe, &self.storage_slot, self.storage_attempt) {
+            timeout = timeout.min(STORAGE_RETRY_PERIOD.saturating_sub(attempt.elapsed()));
+        }
+
+        timeout
+    }
+
+    /// Handles one event, then redraws if anything changed
+    pub fn step<B: Backend>(&mut self, terminal: &mut Terminal<B>, current_events: &mut Option<EventType>, event: Option<PhoneEvent>) -> anyhow::Result<()> {
+        // An app failing on an event is reported, the phone keeps going
+        if let Err(error) = self.handle_phone_event(current_events, event) {
+            self.report_error("Event failed", error);
+        }
+
+        if self.phone_data.toast.as_ref().is_some_and(|toast| toast.expires <= Instant::now()) {
+            self.phone_data.toast = None;
+            self.phone_data.redraw.mark_dirty();
+        }
+
+        // After the event, which may have connected the WiFi
+        if let Err(error) = self.system_check() {
+            self.report_error("System check failed", error);
+        }
+
+        self.storage_check();
+
+        // Suspending runs app code
+        if let Err(error) = self.memory_check() {
+            self.report_error("Memory check failed", error);
+        }
+
+        self.phone_data.touch.lock().unwrap().screen = self.phone_data.screen;
+
+        if self.phone_data.redraw.should_redraw(self.phone_data.time.now()) {
+            self.redraw(terminal, current_events)?;
+        }
+
+        Ok(())
+    }
+
+    fn report_error(&mut self, context: &str, error: anyhow::Error) {
+        warn!("{}: {}", context, error);
+        self.phone_data.show_toast(Toast::error(&error.to_string()));
+    }
+
+    fn handle_phone_event(&mut self, current_events: &mut Option<EventType>, event: Option<PhoneEvent>) -> anyhow::Result<()> {
+        let state = match event {
+            Some(PhoneEvent::Gesture(gesture)) => match current_events {
+                Some(EventType::List(clickable_areas)) => {
+                    self.phone_data.redraw.mark_dirty();
+                    self.handle_gesture(gesture, clickable_areas)?
+                },
+                None => None,
+            },
+            Some(PhoneEvent::TouchHealth(health)) => {
+                self.phone_data.touch_health = health;
+                self.phone_data.redraw.mark_dirty();
+                None
+            },
+            Some(PhoneEvent::App(event)) => {
+                self.phone_data.redraw.mark_dirty();
+                self.handle_posted_event(event.as_ref())?
+            },
+            Some(PhoneEvent::Notify(notification)) => {
+                self.phone_data.notify(notification);
+                None
+            },
+            Some(PhoneEvent::SettingChanged(change)) => {
+                self.phone_data.redraw.mark_dirty();
+                self.handle_setting_change(&change);
+                None
+            },
+            Some(PhoneEvent::System) | None => None,
+        };
+
+        if let Some(state) = state {
+            self.set_state(state)?;
+        }
+
+        Ok(())
+    }
+
+    /// Suspends the background apps when the heap runs low
+    pub fn memory_check(&mut self) -> anyhow::Result<()> {
+        let Some(free_memory) = free_memory() else {
+            return Ok(());
+        };
+
+        let memory_low = free_memory < LOW_MEMORY_THRESHOLD;
+
+        if memory_low && !self.memory_low {
+            warn!("{} bytes of free memory left, suspending background apps", free_memory);
+            self.suspend_background_apps()?;
+        }
+
+        self.memory_low = memory_low;
+
+        Ok(())
+    }
+
+    /// Every app is suspended even when some fail, the first failure is returned
+    pub fn suspend_background_apps(&mut self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+
+        for (index, app) in self.apps.iter_mut().enumerate() {
+            if self.state != PhoneState::InApp(index) && !self.disabled_apps.contains_key(app.app_name()) {
+                self.phone_data.services.stop_app(app.app_name());
+
+                if let Err(error) = block_on(app.suspend(&mut self.phone_data)) {
+                    warn!("Could not suspend {}: {}", app.app_name(), error);
+
+                    if result.is_ok() {
+                        result = Err(error.context(format!("Could not suspend {}", app.app_name())));
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The terminal only hands the cells that changed since the last frame to the display
+    pub fn redraw<B: Backend>(&mut self, terminal: &mut Terminal<B>, current_events: &mut Option<EventType>) -> anyhow::Result<()> {
         terminal.draw(|frame| {
-            current_events = self.handle_draw(frame)
+            *current_events = Some(self.handle_draw(frame))
         })?;
 
-        loop {
-            self.system_check()?;
-
-            if let Some(events) = &current_events {
-                let state = match &events {
-                    EventType::Auto(event) => {
-                        self.handle_auto_event(event)?
-                    },
-                    EventType::List(clickable_areas) => {
-                        if let Ok(touches) = touch_receiver.try_recv() {
-                            let state = match self.format_touches(&touches) {
-                                None => None,
-                                Some(touch) => self.handle_touch(touch, clickable_areas)?
-                            };
-
-                            state
-                        }
-                        else {
-                            None
-                        }
-                    }
-                };
+        self.phone_data.redraw.drawn(self.phone_data.time.now());
 
-                if let Some(state) = state {
-                    self.state = state;
-                }
+        Ok(())
+    }
+
+    /// Feeds recorded touches to the phone, without waiting between them
+    pub fn replay<B: Backend>(&mut self, terminal: &mut Terminal<B>, records: Vec<TouchRecord>) -> anyhow::Result<()> {
+        let mut gesture_recognizer = GestureRecognizer::new();
+        let replay_start = Instant::now();
+        let mut current_events = None;
+
+        self.redraw(terminal, &mut current_events)?;
+
+        for record in records {
+            let now = replay_start + Duration::from_millis(record.timestamp_ms);
+
+            let gestures = self.phone_data.touch.lock().unwrap().recognize(&mut gesture_recognizer, &record.touches, now);
+
+            for gesture in gestures {
+                self.step(terminal, &mut current_events, Some(PhoneEvent::Gesture(gesture)))?;
             }
 
-            terminal.draw(|frame| {
-                current_events = self.handle_draw(frame)
-            })?;
+            // Also let the phone settle, e.g. handle the events apps posted following the gesture
+            let mut steps = 0;
+            loop {
+                self.phone_data.runtime.wait_idle();
+
+                let Ok(event) = self.events.try_recv() else {
+                    break;
+                };
+
+                if steps == MAX_REPLAY_STEPS {
+                    return Err(anyhow!("Replay stuck at {} ms", record.timestamp_ms));
+                }
 
-            sleep(Duration::from_millis(100));
+                self.step(terminal, &mut current_events, Some(event))?;
+                steps += 1;
+            }
         }
+
+        Ok(())
     }
-    pub fn handle_draw(&mut self, frame: &mut Frame) -> Option<EventType> {
+
+    pub fn handle_draw(&mut self, frame: &mut Frame) -> EventType {
         info!("Redraw");
 
-        if let Ok(current_events) = self.draw(frame) {
-            Some(current_events)
-        }
-        else {
-            None
+        match self.draw(frame) {
+            Ok(current_events) => current_events,
+            // Shown to the user like the app failures, OK leaves the broken screen, e.g. the shade
+            Err(error) => {
+                warn!("Failed to render: {}", error);
+                self.phone_data.show_dialog(Dialog::alert("Screen failed", &error.to_string()).on_confirm(CoreEvent::Back));
+
+                let dialog_events = self.phone_data.dialogs.first().map(|dialog| dialog.render(frame)).unwrap_or_default();
+                EventType::List(dialog_events)
+            },
         }
     }
 
     pub fn system_check(&mut self) -> anyhow::Result<()> {
-        let wifi_state = match &self.phone_data.wifi {
-            None => WifiState::NotInitialized,
+        // Busy with a background task, e.g. a scan, checked again on its completion
+        let wifi = match &self.phone_data.wifi {
+            None => None,
+            Some(wifi) => match wifi.try_lock() {
+                Ok(wifi) => Some(wifi),
+                Err(_) => return Ok(()),
+            },
+        };
+
+        let wifi_state = match wifi {
+            None => match self.phone_data.wifi_hardware {
+                Detection::Missing => WifiState::Unavailable,
+                _ => WifiState::NotInitialized,
+            },
             Some(wifi) => match wifi.get_configuration() {
                 Err(_) => WifiState::NotConnected,
                 Ok(wifi_configuration) => match wifi_configuration.as_client_conf_ref() {
@@ -158,15 +719,14 @@ impl Phone<'_> {
 
         if self.phone_data.wifi_state != wifi_state {
             match wifi_state {
-                WifiState::Connected(_) if self.phone_data.ntp.is_none() => {
-                    let ntp = EspSntp::new_default()?;
-                    while ntp.get_sync_status() != SyncStatus::Completed {}
-                    self.phone_data.ntp = Some(ntp);
+                WifiState::Connected(_) if !self.phone_data.time.is_synchronized() => {
+                    self.phone_data.time.synchronize()?;
                 }
-                _ => self.phone_data.ntp = None,
+                _ => self.phone_data.time.desynchronize(),
             }
 
             self.phone_data.wifi_state = wifi_state;
+            self.phone_data.redraw.mark_dirty();
         }
 
         Ok(())
@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use esp_idf_svc::fs::fatfs::Fatfs;
+use esp_idf_svc::hal::gpio::{AnyInputPin, AnyOutputPin};
+use esp_idf_svc::hal::peripheral::Peripheral;
+use esp_idf_svc::hal::sd::{SdCardConfiguration, SdCardDriver};
+use esp_idf_svc::hal::sd::spi::SdSpiHostDriver;
+use esp_idf_svc::hal::spi::SpiDriver;
+use esp_idf_svc::io::vfs::MountedFatfs;
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_svc::sntp::{EspSntp, SyncStatus};
+use esp_idf_svc::wifi::{AccessPointInfo, Configuration, EspWifi};
+use crate::drivers::ft6206::FT6206;
+use crate::drivers::xpt2046::XPT2046;
+use crate::drivers::touch::TouchPoint;
+use crate::platform::{SettingsStore, Storage, StorageSlot, TimeSource, TouchInput, WifiController};
+
+const SD_CARD_SPEED_KHZ: u32 = 4_000;
+/// At most 15 characters each
+const NVS_NAMESPACE: &str = "phone_os";
+const NVS_SETTINGS_KEY: &str = "settings";
+
+pub type SdCard = MountedFatfs<Fatfs<SdCardDriver<SdSpiHostDriver<'static, &'static SpiDriver<'static>>>>>;
+
+pub fn free_heap() -> usize {
+    unsafe { esp_idf_svc::sys::esp_get_free_heap_size() as usize }
+}
+
+impl TouchInput for FT6206 {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        FT6206::read_touches(self)
+    }
+
+    fn reinit(&mut self) -> anyhow::Result<()> {
+        self.configure()
+    }
+
+    fn has_interrupt(&self) -> bool {
+        FT6206::has_interrupt(self)
+    }
+
+    fn wait_for_interrupt(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        FT6206::wait_for_interrupt(self, timeout)
+    }
+}
+
+impl TouchInput for XPT2046 {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        XPT2046::read_touches(self)
+    }
+
+    fn has_interrupt(&self) -> bool {
+        XPT2046::has_interrupt(self)
+    }
+
+    fn wait_for_interrupt(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        XPT2046::wait_for_interrupt(self, timeout)
+    }
+}
+
+impl WifiController for EspWifi<'static> {
+    fn scan(&mut self) -> anyhow::Result<Vec<AccessPointInfo>> {
+        Ok(EspWifi::scan(self)?)
+    }
+
+    fn get_configuration(&self) -> anyhow::Result<Configuration> {
+        Ok(EspWifi::get_configuration(self)?)
+    }
+
+    fn set_configuration(&mut self, configuration: &Configuration) -> anyhow::Result<()> {
+        Ok(EspWifi::set_configuration(self, configuration)?)
+    }
+
+    fn connect(&mut self) -> anyhow::Result<()> {
+        Ok(EspWifi::connect(self)?)
+    }
+
+    fn is_connected(&self) -> anyhow::Result<bool> {
+        Ok(EspWifi::is_connected(self)?)
+    }
+}
+
+pub struct SdCardStorage {
+    _fs: SdCard,
+    mount_point: PathBuf,
+}
+
+impl SdCardStorage {
+    pub fn mount(sd_card_driver: SdCardDriver<SdSpiHostDriver<'static, &'static SpiDriver<'static>>>, mount_point: &str) -> anyhow::Result<Self> {
+        let fs = MountedFatfs::mount(Fatfs::new_sdcard(0, sd_card_driver)?, mount_point, 10)?;
+
+        Ok(SdCardStorage {
+            _fs: fs,
+            mount_point: PathBuf::from(mount_point),
+        })
+    }
+}
+
+impl Storage for SdCardStorage {
+    fn root(&self) -> &Path {
+        &self.mount_point
+    }
+}
+
+/// Settings blob in the default NVS partition
+pub struct NvsSettings(EspNvs<NvsDefault>);
+
+impl NvsSettings {
+    pub fn new(partition: EspDefaultNvsPartition) -> anyhow::Result<Self> {
+        Ok(NvsSettings(EspNvs::new(partition, NVS_NAMESPACE, true)?))
+    }
+}
+
+impl SettingsStore for NvsSettings {
+    fn load(&self) -> anyhow::Result<Option<String>> {
+        let Some(length) = self.0.blob_len(NVS_SETTINGS_KEY)? else {
+            return Ok(None);
+        };
+
+        let mut buffer = vec![0; length];
+
+        match self.0.get_blob(NVS_SETTINGS_KEY, &mut buffer)? {
+            Some(content) => Ok(Some(String::from_utf8(content.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&mut self, content: &str) -> anyhow::Result<()> {
+        self.0.set_blob(NVS_SETTINGS_KEY, content.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// SD card on a shared SPI bus, a new driver is built for each attempt as a failed mount consumes it
+pub struct SdCardSlot {
+    spi_driver: &'static SpiDriver<'static>,
+    cs: AnyOutputPin,
+    mount_point: &'static str,
+}
+
+impl SdCardSlot {
+    pub fn new(spi_driver: &'static SpiDriver<'static>, cs: AnyOutputPin, mount_point: &'static str) -> Self {
+        SdCardSlot {
+            spi_driver,
+            cs,
+            mount_point,
+        }
+    }
+}
+
+impl StorageSlot for SdCardSlot {
+    fn mount(&mut self) -> anyhow::Result<Box<dyn Storage + Send>> {
+        let mut configuration = SdCardConfiguration::new();
+        configuration.speed_khz = SD_CARD_SPEED_KHZ;
+
+        // Only one driver holds the pin at a time, the previous one was dropped with its failed mount
+        let cs = unsafe { self.cs.clone_unchecked() };
+
+        let sd_card_driver = SdCardDriver::new_spi(
+            SdSpiHostDriver::new(
+                self.spi_driver,
+                Some(cs),
+                None::<AnyInputPin>,
+                None::<AnyInputPin>,
+                None::<AnyInputPin>,
+                None,
+            )?,
+            &configuration,
+        )?;
+
+        Ok(Box::new(SdCardStorage::mount(sd_card_driver, self.mount_point)?))
+    }
+}
+
+#[derive(Default)]
+pub struct SntpTimeSource {
+    sntp: Option<EspSntp<'static>>,
+}
+
+impl TimeSource for SntpTimeSource {
+    fn synchronize(&mut self) -> anyhow::Result<()> {
+        let sntp = EspSntp::new_default()?;
+        while sntp.get_sync_status() != SyncStatus::Completed {}
+        self.sntp = Some(sntp);
+
+        Ok(())
+    }
+
+    fn desynchronize(&mut self) {
+        self.sntp = None;
+    }
+
+    fn is_synchronized(&self) -> bool {
+        self.sntp.is_some()
+    }
+}
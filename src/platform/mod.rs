@@ -0,0 +1,144 @@
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+use embedded_svc::wifi::{AccessPointInfo, Configuration};
+use crate::drivers::touch::TouchPoint;
+
+pub mod recording;
+pub mod supervisor;
+
+#[cfg(feature = "esp")]
+pub mod esp;
+
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+/// Source of raw touch points, polled from the touch thread
+pub trait TouchInput: Send {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>>;
+
+    /// Brings the controller back to a known state after repeated read failures
+    fn reinit(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Whether `wait_for_interrupt` is backed by an interrupt line, otherwise the controller is polled
+    fn has_interrupt(&self) -> bool {
+        false
+    }
+
+    /// Blocks until the controller signals new touches or the timeout elapses, returns whether it signalled
+    fn wait_for_interrupt(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        sleep(timeout);
+        Ok(false)
+    }
+}
+
+/// Shared with the background tasks, e.g. scanning, see `Runtime`
+pub type SharedWifi = Arc<Mutex<dyn WifiController + Send>>;
+
+/// WiFi station, mirrors the subset of the `EspWifi` API used by the phone
+pub trait WifiController {
+    fn scan(&mut self) -> anyhow::Result<Vec<AccessPointInfo>>;
+    fn get_configuration(&self) -> anyhow::Result<Configuration>;
+    fn set_configuration(&mut self, configuration: &Configuration) -> anyhow::Result<()>;
+    fn connect(&mut self) -> anyhow::Result<()>;
+    fn is_connected(&self) -> anyhow::Result<bool>;
+}
+
+/// Persistent storage, paths are relative to the storage root
+pub trait Storage {
+    fn root(&self) -> &Path;
+
+    fn read_to_string(&self, path: &Path) -> anyhow::Result<Option<String>> {
+        let path = self.root().join(path);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read_to_string(path)?))
+    }
+
+    fn write(&self, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+        fs::write(self.root().join(path), content)?;
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(self.root().join(path))?;
+        file.write_all(content)?;
+        Ok(())
+    }
+
+    /// Does nothing if the file does not exist
+    fn remove(&self, path: &Path) -> anyhow::Result<()> {
+        let path = self.root().join(path);
+
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Along with its missing parents
+    fn create_dir(&self, path: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(self.root().join(path))?;
+        Ok(())
+    }
+}
+
+/// Persistent blob holding the settings, the NVS partition on the phone, see `Settings`
+pub trait SettingsStore {
+    fn load(&self) -> anyhow::Result<Option<String>>;
+    fn save(&mut self, content: &str) -> anyhow::Result<()>;
+}
+
+/// Where the SD card goes, mounted again until a card is inserted, see `Phone::storage_check`
+///
+/// Sent to the unblock workers for each attempt but the first one
+pub trait StorageSlot: Send {
+    fn mount(&mut self) -> anyhow::Result<Box<dyn Storage + Send>>;
+}
+
+/// Free heap in bytes, unknown on the host
+pub fn free_memory() -> Option<usize> {
+    #[cfg(feature = "esp")]
+    return Some(esp::free_heap());
+
+    #[cfg(not(feature = "esp"))]
+    None
+}
+
+/// Wall clock, possibly synchronized over the network
+pub trait TimeSource {
+    /// Blocks until the clock is synchronized
+    fn synchronize(&mut self) -> anyhow::Result<()>;
+    fn desynchronize(&mut self);
+    fn is_synchronized(&self) -> bool;
+
+    /// Time elapsed since the UNIX epoch
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/// Host clock, which is always considered synchronized
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn synchronize(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn desynchronize(&mut self) {}
+
+    fn is_synchronized(&self) -> bool {
+        true
+    }
+}
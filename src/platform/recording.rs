@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::fs::{read_to_string, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::drivers::touch::TouchPoint;
+use crate::platform::TouchInput;
+
+/// One touch controller read, stored as a JSON line.
+/// Empty reads are only recorded right after a non-empty one, as they mean every finger was lifted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchRecord {
+    /// Milliseconds since the recording started
+    pub timestamp_ms: u64,
+    pub touches: Vec<TouchPoint>,
+}
+
+/// Forwards the touches of the wrapped controller and appends them to a recording file.
+/// Recording errors are only logged, the touches still reach the phone.
+pub struct TouchRecorder<T: TouchInput> {
+    inner: T,
+    /// Dropped on the first error, e.g. without an SD card
+    file: Option<File>,
+    start: Instant,
+    touching: bool,
+}
+
+impl<T: TouchInput> TouchRecorder<T> {
+    pub fn new(inner: T, path: &Path) -> Self {
+        let file = match File::create(path) {
+            Ok(file) => {
+                info!("Recording touches to {}", path.display());
+                Some(file)
+            },
+            Err(error) => {
+                warn!("Touches not recorded, could not create {}: {}", path.display(), error);
+                None
+            },
+        };
+
+        TouchRecorder {
+            inner,
+            file,
+            start: Instant::now(),
+            touching: false,
+        }
+    }
+
+    fn record(&mut self, touches: &[TouchPoint]) -> anyhow::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        let record = TouchRecord {
+            timestamp_ms: self.start.elapsed().as_millis() as u64,
+            touches: touches.to_vec(),
+        };
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<T: TouchInput> TouchInput for TouchRecorder<T> {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        let touches = self.inner.read_touches()?;
+
+        if !touches.is_empty() || self.touching {
+            self.touching = !touches.is_empty();
+
+            // Not a controller failure, which would reset the touch controller
+            if let Err(error) = self.record(&touches) {
+                warn!("Touch recording stopped: {}", error);
+                self.file = None;
+            }
+        }
+
+        Ok(touches)
+    }
+
+    fn reinit(&mut self) -> anyhow::Result<()> {
+        self.inner.reinit()
+    }
+
+    fn has_interrupt(&self) -> bool {
+        self.inner.has_interrupt()
+    }
+
+    fn wait_for_interrupt(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        self.inner.wait_for_interrupt(timeout)
+    }
+}
+
+/// Plays a recording back in place of a touch controller, respecting its timestamps.
+/// The last played touches are repeated until the next record is due, like a finger held down.
+pub struct TouchReplay {
+    records: VecDeque<TouchRecord>,
+    start: Option<Instant>,
+    current: Vec<TouchPoint>,
+}
+
+impl TouchReplay {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let records = parse_recording(&read_to_string(path)?)?;
+
+        info!("Replaying {} touch records from {}", records.len(), path.display());
+
+        Ok(Self::from_records(records))
+    }
+
+    pub fn from_records(records: Vec<TouchRecord>) -> Self {
+        TouchReplay {
+            records: records.into(),
+            start: None,
+            current: Vec::new(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl TouchInput for TouchReplay {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        let elapsed_ms = self.start.get_or_insert_with(Instant::now).elapsed().as_millis() as u64;
+
+        if let Some(record) = self.records.front() {
+            if record.timestamp_ms <= elapsed_ms {
+                self.current = self.records.pop_front().unwrap().touches;
+            }
+        }
+
+        Ok(self.current.clone())
+    }
+}
+
+pub fn parse_recording(content: &str) -> anyhow::Result<Vec<TouchRecord>> {
+    let mut records = Vec::new();
+
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        records.push(serde_json::from_str(line)?);
+    }
+
+    Ok(records)
+}
@@ -0,0 +1,207 @@
+use std::fs;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use embedded_svc::wifi::{AccessPointInfo, AuthMethod, Configuration};
+use log::info;
+use crate::drivers::display::DisplayModel;
+use crate::drivers::touch::{TouchEvent, TouchPoint};
+use crate::platform::{SettingsStore, Storage, TouchInput, WifiController};
+
+/// Fake WiFi stack with a couple of always reachable access points
+pub struct SimulatedWifi {
+    access_points: Vec<AccessPointInfo>,
+    configuration: Configuration,
+    connected: bool,
+}
+
+impl SimulatedWifi {
+    pub fn new() -> Self {
+        let access_points = [("Wokwi-GUEST", AuthMethod::None, -40), ("Simulated-WPA2", AuthMethod::WPA2Personal, -65)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (ssid, auth_method, signal_strength))| AccessPointInfo {
+                ssid: heapless::String::from_str(ssid).unwrap(),
+                bssid: [2, 0, 0, 0, 0, index as u8],
+                channel: 6,
+                signal_strength,
+                auth_method: Some(auth_method),
+                ..Default::default()
+            })
+            .collect();
+
+        SimulatedWifi {
+            access_points,
+            configuration: Configuration::None,
+            connected: false,
+        }
+    }
+}
+
+impl WifiController for SimulatedWifi {
+    fn scan(&mut self) -> anyhow::Result<Vec<AccessPointInfo>> {
+        Ok(self.access_points.clone())
+    }
+
+    fn get_configuration(&self) -> anyhow::Result<Configuration> {
+        Ok(self.configuration.clone())
+    }
+
+    fn set_configuration(&mut self, configuration: &Configuration) -> anyhow::Result<()> {
+        self.configuration = configuration.clone();
+        self.connected = false;
+        Ok(())
+    }
+
+    fn connect(&mut self) -> anyhow::Result<()> {
+        if let Some(client_configuration) = self.configuration.as_client_conf_ref() {
+            self.connected = self.access_points.iter().any(|ap| ap.ssid == client_configuration.ssid);
+            info!("Simulated connection to {}: {}", client_configuration.ssid, self.connected);
+        }
+
+        Ok(())
+    }
+
+    fn is_connected(&self) -> anyhow::Result<bool> {
+        Ok(self.connected)
+    }
+}
+
+impl Default for SimulatedWifi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Touch controller fed by the simulator window mouse events
+pub struct SimulatorTouch {
+    receiver: Receiver<TouchPoint>,
+    /// Received while waiting for an interrupt, not read yet
+    pending: Option<TouchPoint>,
+    /// Mouse button held down
+    current: Option<TouchPoint>,
+}
+
+/// Converts simulator window mouse events into raw FT6206-like touch points
+#[derive(Clone)]
+pub struct SimulatorTouchSender {
+    sender: Sender<TouchPoint>,
+    /// Touches are reported on its portrait panel
+    model: DisplayModel,
+}
+
+impl SimulatorTouch {
+    pub fn new(model: DisplayModel) -> (SimulatorTouch, SimulatorTouchSender) {
+        let (sender, receiver) = unbounded();
+
+        (SimulatorTouch { receiver, pending: None, current: None }, SimulatorTouchSender { sender, model })
+    }
+}
+
+impl TouchInput for SimulatorTouch {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        let received = self.pending.take().map_or_else(|| self.receiver.try_recv(), Ok);
+
+        let touches = match received {
+            Ok(touch) => {
+                self.current = match touch.event {
+                    Some(TouchEvent::Release) => None,
+                    _ => Some(touch),
+                };
+
+                vec![touch]
+            },
+            // Like the FT6206, keep reporting the contact while the button is held
+            Err(_) => self.current
+                .iter()
+                .map(|touch| TouchPoint { event: Some(TouchEvent::Move), ..*touch })
+                .collect()
+        };
+
+        Ok(touches)
+    }
+
+    /// Mouse events play the role of the INT line
+    fn has_interrupt(&self) -> bool {
+        true
+    }
+
+    fn wait_for_interrupt(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        if self.pending.is_none() {
+            self.pending = self.receiver.recv_timeout(timeout).ok();
+        }
+
+        Ok(self.pending.is_some())
+    }
+}
+
+impl SimulatorTouchSender {
+    /// Takes a pixel position on the portrait panel and mirrors it like the FT6206 does, see `TouchCalibration::for_model`
+    pub fn send(&self, x: i32, y: i32, event: TouchEvent) {
+        let (width, height) = self.model.pixel_size();
+
+        let x = x.clamp(0, width as i32 - 1) as u16;
+        let y = y.clamp(0, height as i32 - 1) as u16;
+
+        let touch = TouchPoint {
+            x: width - 1 - x,
+            y: height - 1 - y,
+            id: 0,
+            event: Some(event),
+        };
+
+        // The phone may have been dropped while the window is closing
+        let _ = self.sender.send(touch);
+    }
+}
+
+/// Local directory standing in for the SD card mount point
+pub struct HostDirectory {
+    path: PathBuf,
+}
+
+impl HostDirectory {
+    pub fn mount(path: &Path) -> anyhow::Result<Self> {
+        create_dir_all(path)?;
+        info!("Using {} as SD card", path.display());
+
+        Ok(HostDirectory {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Storage for HostDirectory {
+    fn root(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// File standing in for the NVS partition, kept out of the SD card directory
+pub struct HostSettings {
+    path: PathBuf,
+}
+
+impl HostSettings {
+    pub fn new(path: &Path) -> Self {
+        HostSettings {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl SettingsStore for HostSettings {
+    fn load(&self) -> anyhow::Result<Option<String>> {
+        match self.path.exists() {
+            true => Ok(Some(fs::read_to_string(&self.path)?)),
+            false => Ok(None),
+        }
+    }
+
+    fn save(&mut self, content: &str) -> anyhow::Result<()> {
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::thread::sleep;
+use std::time::Duration;
+use log::{info, warn};
+use crate::drivers::touch::TouchPoint;
+use crate::phone::TouchHealth;
+use crate::platform::TouchInput;
+
+/// Consecutive failed reads after which the controller is re-initialised
+pub const REINIT_AFTER_FAILURES: u32 = 5;
+/// Slower polling while the controller does not even re-initialise
+const UNAVAILABLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// The controller is read at least this often while idle, in case an interrupt was missed
+const IDLE_INTERRUPT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Touches found after interrupt timeouts, after which the interrupt line is considered unwired
+pub const MISSED_INTERRUPTS_BEFORE_POLLING: u32 = 3;
+
+/// Touch thread timings
+#[derive(Debug, Clone, Copy)]
+pub struct TouchConfig {
+    /// Delay between two reads while a finger is down, or at all times without an interrupt line
+    pub poll_interval: Duration,
+    /// Delay between an interrupt and the read, letting the controller settle
+    pub debounce: Duration,
+}
+
+impl Default for TouchConfig {
+    fn default() -> Self {
+        TouchConfig {
+            poll_interval: Duration::from_millis(20),
+            debounce: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Keeps a touch controller usable across read errors, retrying and re-initialising it when needed
+pub struct TouchSupervisor<T: TouchInput> {
+    inner: T,
+    config: TouchConfig,
+    failures: u32,
+    health: TouchHealth,
+    /// The last wait for an interrupt timed out
+    timed_out: bool,
+    missed_interrupts: u32,
+    /// The interrupt line never fires, see `MISSED_INTERRUPTS_BEFORE_POLLING`
+    interrupt_unwired: bool,
+}
+
+impl<T: TouchInput> TouchSupervisor<T> {
+    pub fn new(inner: T, config: TouchConfig) -> Self {
+        TouchSupervisor {
+            inner,
+            config,
+            failures: 0,
+            health: TouchHealth::Healthy,
+            timed_out: false,
+            missed_interrupts: 0,
+            interrupt_unwired: false,
+        }
+    }
+
+    /// Whether the controller is read at every poll interval, instead of waiting for its interrupt line
+    pub fn is_polling(&self) -> bool {
+        !self.inner.has_interrupt() || self.interrupt_unwired
+    }
+
+    pub fn health(&self) -> TouchHealth {
+        self.health
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        match self.health {
+            TouchHealth::Unavailable => UNAVAILABLE_POLL_INTERVAL,
+            _ => self.config.poll_interval,
+        }
+    }
+
+    /// Waits for the next read: sleeps on the interrupt line while idle, polls while touching
+    pub fn wait(&mut self, touching: bool) {
+        if !self.is_polling() && !touching && self.health == TouchHealth::Healthy {
+            match self.inner.wait_for_interrupt(IDLE_INTERRUPT_TIMEOUT) {
+                Ok(interrupted) => {
+                    self.timed_out = !interrupted;
+
+                    if interrupted {
+                        sleep(self.config.debounce);
+                    }
+
+                    return;
+                },
+                Err(error) => warn!("Waiting for a touch interrupt failed: {}", error),
+            }
+        }
+
+        sleep(self.poll_interval());
+    }
+
+    /// Failed reads are reported as no touch, which also releases any ongoing gesture
+    pub fn read_touches(&mut self) -> Vec<TouchPoint> {
+        match self.inner.read_touches() {
+            Ok(touches) => {
+                if self.health != TouchHealth::Healthy {
+                    info!("Touch controller recovered after {} failed reads", self.failures);
+                }
+
+                self.failures = 0;
+                self.health = TouchHealth::Healthy;
+
+                // A finger was already down without any interrupt
+                if std::mem::take(&mut self.timed_out) && !touches.is_empty() {
+                    self.missed_interrupt();
+                }
+
+                touches
+            },
+            Err(error) => {
+                self.failures += 1;
+                warn!("Touch read failed ({} in a row): {}", self.failures, error);
+
+                if self.failures % REINIT_AFTER_FAILURES == 0 {
+                    match self.inner.reinit() {
+                        Ok(()) => {
+                            info!("Touch controller re-initialised");
+                            self.health = TouchHealth::Degraded;
+                        },
+                        Err(error) => {
+                            warn!("Could not re-initialise the touch controller: {}", error);
+                            self.health = TouchHealth::Unavailable;
+                        }
+                    }
+                }
+                else if self.health == TouchHealth::Healthy {
+                    self.health = TouchHealth::Degraded;
+                }
+
+                Vec::new()
+            }
+        }
+    }
+
+    fn missed_interrupt(&mut self) {
+        self.missed_interrupts += 1;
+
+        if self.missed_interrupts >= MISSED_INTERRUPTS_BEFORE_POLLING && !self.interrupt_unwired {
+            warn!("The touch interrupt line does not fire, polling the controller instead");
+            self.interrupt_unwired = true;
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::thread::Thread;
+use anyhow::anyhow;
+use crossbeam_channel::{unbounded, Sender};
+use log::warn;
+
+/// Big enough for the WiFi driver calls made by background tasks, and for unwinding their panics on the host
+const TASK_STACK_SIZE: usize = match cfg!(panic = "unwind") {
+    true => 64 * 1024,
+    false => 16 * 1024,
+};
+/// Threads running the calls of `unblock`, e.g. a WiFi scan next to a service poll
+pub const UNBLOCK_WORKERS: usize = 2;
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Started by the first `unblock`, calls wait in the queue for a free worker
+static UNBLOCK_QUEUE: OnceLock<Sender<Job>> = OnceLock::new();
+
+/// Runs app background tasks on their own thread, so that the UI stays responsive.
+/// Any future works, e.g. the esp-idf-svc async drivers or embassy-time timers, as esp-idf-svc provides their time driver.
+#[derive(Clone)]
+pub struct Runtime {
+    queue: Sender<Arc<Task>>,
+    /// Spawned tasks not finished yet
+    pending: Arc<(Mutex<usize>, Condvar)>,
+}
+
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    queue: Sender<Arc<Task>>,
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.queue.clone().send(self).ok();
+    }
+}
+
+impl Runtime {
+    /// Panics if the thread cannot be spawned, which only happens out of memory at boot
+    pub fn new() -> Self {
+        let (queue, tasks) = unbounded::<Arc<Task>>();
+        let pending = Arc::new((Mutex::new(0), Condvar::new()));
+        let tasks_pending = pending.clone();
+
+        thread::Builder::new()
+            .name(String::from("runtime"))
+            .stack_size(TASK_STACK_SIZE)
+            .spawn(move || {
+                for task in tasks {
+                    let mut future = task.future.lock().unwrap();
+
+                    // Already finished, woken once more
+                    let Some(running) = future.as_mut() else {
+                        continue;
+                    };
+
+                    let waker = Waker::from(task.clone());
+
+                    // A panicking task is dropped, the others keep running
+                    let finished = match catch_unwind(AssertUnwindSafe(|| running.as_mut().poll(&mut Context::from_waker(&waker)))) {
+                        Ok(poll) => poll.is_ready(),
+                        Err(_) => {
+                            warn!("Background task panicked, dropped");
+                            true
+                        }
+                    };
+
+                    if finished {
+                        *future = None;
+
+                        let (count, finished) = &*tasks_pending;
+                        *count.lock().unwrap() -= 1;
+                        finished.notify_all();
+                    }
+                }
+            })
+            .expect("Could not spawn the runtime thread");
+
+        Runtime {
+            queue,
+            pending,
+        }
+    }
+
+    pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        *self.pending.0.lock().unwrap() += 1;
+
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(future))),
+            queue: self.queue.clone(),
+        });
+
+        if self.queue.send(task).is_err() {
+            warn!("Runtime stopped, task dropped");
+        }
+    }
+
+    /// Blocks until every spawned task is finished, used to replay touches deterministically
+    pub fn wait_idle(&self) {
+        let (count, finished) = &*self.pending;
+        let _idle = finished.wait_while(count.lock().unwrap(), |count| *count > 0).unwrap();
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs a future on the current thread, used by the UI thread for the app methods
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs a blocking driver call on one of the `UNBLOCK_WORKERS` threads, so that it does not hold other tasks back.
+/// A panic of the call is returned as an error, and the worker stays available.
+pub async fn unblock<T: Send + 'static>(function: impl FnOnce() -> T + Send + 'static) -> anyhow::Result<T> {
+    let state = Arc::new(Mutex::new((None, None::<Waker>)));
+    let thread_state = state.clone();
+
+    let job: Job = Box::new(move || {
+        let output = catch_unwind(AssertUnwindSafe(function));
+
+        let mut state = thread_state.lock().unwrap();
+        state.0 = Some(output);
+
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    });
+
+    if unblock_queue()?.send(job).is_err() {
+        return Err(anyhow!("The unblock workers stopped"));
+    }
+
+    let output = std::future::poll_fn(|context| {
+        let mut state = state.lock().unwrap();
+
+        match state.0.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.1 = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }).await;
+
+    output.map_err(|_| anyhow!("The blocking call panicked"))
+}
+
+fn unblock_queue() -> anyhow::Result<&'static Sender<Job>> {
+    if let Some(queue) = UNBLOCK_QUEUE.get() {
+        return Ok(queue);
+    }
+
+    let (queue, jobs) = unbounded::<Job>();
+
+    // The workers of a losing concurrent call stop with its queue
+    for index in 0..UNBLOCK_WORKERS {
+        let jobs = jobs.clone();
+
+        thread::Builder::new()
+            .name(format!("unblock-{}", index))
+            .stack_size(TASK_STACK_SIZE)
+            .spawn(move || {
+                for job in jobs {
+                    job();
+                }
+            })?;
+    }
+
+    Ok(UNBLOCK_QUEUE.get_or_init(|| queue))
+}
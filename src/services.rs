@@ -0,0 +1,308 @@
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use anyhow::anyhow;
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use log::{info, warn};
+use crate::events::AppEvent;
+use crate::events::queue::{EventSender, PhoneEvent};
+
+/// Services one app may run at once
+pub const MAX_SERVICES_PER_APP: usize = 4;
+/// Shortest period between two polls, so that no service keeps the CPU busy
+pub const MIN_SERVICE_PERIOD: Duration = Duration::from_millis(100);
+/// Longest poll allowed, the services of every app wait for it, so a service exceeding it is stopped
+pub const MAX_POLL_DURATION: Duration = Duration::from_secs(1);
+/// Big enough for the blocking calls of the pollers, e.g. a web API query, and for unwinding their panics on the host
+const SERVICE_STACK_SIZE: usize = match cfg!(panic = "unwind") {
+    true => 64 * 1024,
+    false => 16 * 1024,
+};
+
+/// Background work polled periodically, even while its app is not displayed
+pub trait Service: Send + 'static {
+    fn period(&self) -> Duration;
+
+    /// Runs on the services thread, the returned event is posted to the apps, see `EventSender::post`.
+    /// The next period starts once it returns, a slow poll delays the other services.
+    /// A poll which panics or takes longer than `MAX_POLL_DURATION` stops its service.
+    fn poll(&mut self) -> anyhow::Result<Option<Box<dyn AppEvent>>>;
+}
+
+/// Posts the same event every period
+pub struct Timer<E: AppEvent + Clone> {
+    pub period: Duration,
+    pub event: E,
+}
+
+impl<E: AppEvent + Clone> Service for Timer<E> {
+    fn period(&self) -> Duration {
+        self.period
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Option<Box<dyn AppEvent>>> {
+        Ok(Some(Box::new(self.event.clone())))
+    }
+}
+
+/// Calls a blocking function every period, e.g. to query a web API, posting the event it returns.
+/// The call must return within `MAX_POLL_DURATION`, e.g. using a timeout for the query.
+pub struct Poller<E: AppEvent, F: FnMut() -> anyhow::Result<Option<E>> + Send + 'static> {
+    pub period: Duration,
+    pub poll: F,
+}
+
+impl<E: AppEvent, F: FnMut() -> anyhow::Result<Option<E>> + Send + 'static> Service for Poller<E, F> {
+    fn period(&self) -> Duration {
+        self.period
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Option<Box<dyn AppEvent>>> {
+        Ok((self.poll)()?.map(|event| Box::new(event) as Box<dyn AppEvent>))
+    }
+}
+
+/// Posts the event whenever the file is created, modified or deleted
+pub struct FileWatcher<E: AppEvent + Clone> {
+    /// Absolute, e.g. `Storage::root` joined with the app file
+    pub path: PathBuf,
+    pub period: Duration,
+    pub event: E,
+    last_modified: Option<Option<SystemTime>>,
+}
+
+impl<E: AppEvent + Clone> FileWatcher<E> {
+    pub fn new(path: PathBuf, period: Duration, event: E) -> Self {
+        FileWatcher {
+            path,
+            period,
+            event,
+            last_modified: None,
+        }
+    }
+}
+
+impl<E: AppEvent + Clone> Service for FileWatcher<E> {
+    fn period(&self) -> Duration {
+        self.period
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Option<Box<dyn AppEvent>>> {
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+
+        // The first poll only records the current state
+        let changed = matches!(self.last_modified, Some(last_modified) if last_modified != modified);
+        self.last_modified = Some(modified);
+
+        Ok(match changed {
+            true => Some(Box::new(self.event.clone())),
+            false => None,
+        })
+    }
+}
+
+struct RunningService {
+    app: &'static str,
+    name: &'static str,
+    stop: Arc<AtomicBool>,
+}
+
+/// Owned by the services thread
+struct ScheduledService {
+    app: &'static str,
+    name: &'static str,
+    service: Box<dyn Service>,
+    next_poll: Instant,
+    stop: Arc<AtomicBool>,
+}
+
+/// Services started by the apps, they keep running until stopped, whatever app is displayed
+pub struct Services {
+    /// Hands the started services to the services thread
+    queue: Sender<ScheduledService>,
+    running: Vec<RunningService>,
+    /// The app the phone is calling, which owns the services it starts, see `AppImpl`
+    app: Option<&'static str>,
+}
+
+impl Services {
+    /// Panics if the thread cannot be spawned, which only happens out of memory at boot
+    pub fn new(events: EventSender) -> Self {
+        let (queue, started) = unbounded();
+
+        thread::Builder::new()
+            .name(String::from("services"))
+            .stack_size(SERVICE_STACK_SIZE)
+            .spawn(move || run_services(started, events))
+            .expect("Could not spawn the services thread");
+
+        Services {
+            queue,
+            running: vec![],
+            app: None,
+        }
+    }
+
+    /// Set by `AppImpl` before each call to the app
+    pub(crate) fn bind(&mut self, app: &'static str) {
+        self.app = Some(app);
+    }
+
+    /// Cleared by `AppImpl` after each call, so the phone itself is never taken for an app
+    pub(crate) fn unbind(&mut self) {
+        self.app = None;
+    }
+
+    /// The app the phone is calling, if any
+    pub(crate) fn bound_app(&self) -> Option<&'static str> {
+        self.app
+    }
+
+    /// Replaces the service of the app with the same name, if any
+    pub fn start(&mut self, name: &'static str, service: impl Service) -> anyhow::Result<()> {
+        let Some(app) = self.app else {
+            return Err(anyhow!("Service {} started outside of an app", name));
+        };
+
+        let period = service.period();
+
+        if period < MIN_SERVICE_PERIOD {
+            return Err(anyhow!("Service {} of {} polls every {:?}, at least {:?} are needed", name, app, period, MIN_SERVICE_PERIOD));
+        }
+
+        if self.running(app).filter(|running| *running != name).count() >= MAX_SERVICES_PER_APP {
+            return Err(anyhow!("{} already runs {} services", app, MAX_SERVICES_PER_APP));
+        }
+
+        self.stop_services(app, Some(name));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let scheduled = ScheduledService {
+            app,
+            name,
+            service: Box::new(service),
+            next_poll: Instant::now() + period,
+            stop: stop.clone(),
+        };
+
+        if self.queue.send(scheduled).is_err() {
+            return Err(anyhow!("Service {} of {} not started, the services thread stopped", name, app));
+        }
+
+        info!("Service {} of {} started", name, app);
+        self.running.push(RunningService {
+            app,
+            name,
+            stop,
+        });
+
+        Ok(())
+    }
+
+    /// The service of the app stops before its next poll
+    pub fn stop(&mut self, name: &'static str) {
+        if let Some(app) = self.app {
+            self.stop_services(app, Some(name));
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        if let Some(app) = self.app {
+            self.stop_services(app, None);
+        }
+    }
+
+    /// When the app is disabled, suspended or has its data cleared
+    pub(crate) fn stop_app(&mut self, app: &'static str) {
+        self.stop_services(app, None);
+    }
+
+    fn stop_services(&mut self, app: &'static str, name: Option<&'static str>) {
+        self.running.retain(|service| match service.app == app && name.map_or(true, |name| service.name == name) {
+            true => {
+                service.stop.store(true, Ordering::Relaxed);
+                info!("Service {} of {} stopped", service.name, app);
+                false
+            },
+            false => true,
+        });
+    }
+
+    /// Names of the app services, without the ones stopped by the services thread
+    pub fn running(&self, app: &'static str) -> impl Iterator<Item = &'static str> + '_ {
+        self.running
+            .iter()
+            .filter(move |service| service.app == app && !service.stop.load(Ordering::Relaxed))
+            .map(|service| service.name)
+    }
+}
+
+// The services thread stops with the queue
+impl Drop for Services {
+    fn drop(&mut self) {
+        for service in &self.running {
+            service.stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Polls the due services one after the other, until the `Services` are dropped
+fn run_services(started: Receiver<ScheduledService>, events: EventSender) {
+    let mut scheduled: Vec<ScheduledService> = vec![];
+
+    loop {
+        scheduled.retain(|service| !service.stop.load(Ordering::Relaxed));
+
+        let received = match scheduled.iter().map(|service| service.next_poll).min() {
+            Some(next_poll) => started.recv_deadline(next_poll),
+            None => started.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match received {
+            Ok(service) => {
+                scheduled.push(service);
+                continue;
+            },
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let now = Instant::now();
+
+        for scheduled in scheduled.iter_mut().filter(|service| service.next_poll <= now) {
+            if scheduled.stop.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = catch_unwind(AssertUnwindSafe(|| scheduled.service.poll()));
+            scheduled.next_poll = Instant::now() + scheduled.service.period();
+
+            // Dropped with the next retain, the app sees it stopped in `Services::running`
+            let Ok(result) = result else {
+                warn!("Service {} of {} panicked, stopped", scheduled.name, scheduled.app);
+                scheduled.stop.store(true, Ordering::Relaxed);
+                continue;
+            };
+
+            if started.elapsed() > MAX_POLL_DURATION {
+                warn!("Service {} of {} polled for {:?}, stopped", scheduled.name, scheduled.app, started.elapsed());
+                scheduled.stop.store(true, Ordering::Relaxed);
+                continue;
+            }
+
+            match result {
+                // Not posted once stopped during the poll
+                Ok(Some(event)) if !scheduled.stop.load(Ordering::Relaxed) => {
+                    events.send(PhoneEvent::App(event)).ok();
+                },
+                Ok(_) => {},
+                Err(error) => warn!("Service {} of {} failed: {}", scheduled.name, scheduled.app, error),
+            }
+        }
+    }
+}
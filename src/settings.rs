@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use anyhow::anyhow;
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use crate::events::queue::EventSender;
+use crate::phone::Phone;
+use crate::platform::{SettingsStore, Storage};
+use crate::runtime::block_on;
+
+/// Backup on the SD card, see `Settings::export`
+pub const SETTINGS_EXPORT_PATH: &str = "settings.json";
+
+/// Values by key, by namespace
+type Values = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// Typed setting of an app, read as its default until set
+pub struct Setting<T> {
+    /// `App::app_name` of the owner
+    pub namespace: &'static str,
+    pub key: &'static str,
+    pub default: T,
+}
+
+impl<T> Setting<T> {
+    pub const fn new(namespace: &'static str, key: &'static str, default: T) -> Self {
+        Setting {
+            namespace,
+            key,
+            default,
+        }
+    }
+}
+
+/// Handled by every app, see `AppHandler::on_setting_changed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingChange {
+    pub namespace: String,
+    pub key: String,
+}
+
+impl SettingChange {
+    pub fn is<T>(&self, setting: &Setting<T>) -> bool {
+        self.namespace == setting.namespace && self.key == setting.key
+    }
+}
+
+/// System settings, namespaced per app and saved on each change.
+/// Apps only reach their own namespace, the one of their `App::app_name`.
+pub struct Settings {
+    values: Values,
+    /// Kept in memory only without it, e.g. in the host tests
+    store: Option<Box<dyn SettingsStore>>,
+    events: EventSender,
+    /// The app the phone is calling and whether it is a core app, see `Services::bind`
+    app: Option<(&'static str, bool)>,
+}
+
+impl Settings {
+    pub fn new(events: EventSender) -> Self {
+        Settings {
+            values: Values::new(),
+            store: None,
+            events,
+            app: None,
+        }
+    }
+
+    /// Set by `AppImpl` before each call to the app
+    pub(crate) fn bind(&mut self, app: &'static str, core: bool) {
+        self.app = Some((app, core));
+    }
+
+    /// Cleared by `AppImpl` after each call
+    pub(crate) fn unbind(&mut self) {
+        self.app = None;
+    }
+
+    /// The phone itself reads every namespace
+    fn can_read(&self, namespace: &str) -> bool {
+        match self.app {
+            None => true,
+            Some((app, _)) => app == namespace,
+        }
+    }
+
+    /// Only the app owning the namespace changes it
+    fn check_write(&self, namespace: &str, key: &str) -> anyhow::Result<()> {
+        match self.app {
+            None => Err(anyhow!("Setting {}/{} changed outside of an app", namespace, key)),
+            Some((app, _)) if app != namespace => Err(anyhow!("{} cannot change the setting {}/{}", app, namespace, key)),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Every namespace at once is left to the system settings, i.e. the core apps, and to the phone for reading
+    fn check_every_namespace(&self, write: bool) -> anyhow::Result<()> {
+        match self.app {
+            Some((_, true)) => Ok(()),
+            Some((app, false)) => Err(anyhow!("{} cannot reach the settings of other apps", app)),
+            None if write => Err(anyhow!("Settings replaced outside of an app")),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the current values with the stored ones, then saves every change to the store
+    ///
+    /// Stored values which cannot be read are dropped for the defaults, overwritten by the next change
+    pub fn load(&mut self, store: Box<dyn SettingsStore>) -> anyhow::Result<()> {
+        if let Some((app, _)) = self.app {
+            return Err(anyhow!("{} cannot load the settings", app));
+        }
+
+        let content = store.load();
+        self.store = Some(store);
+
+        let Some(content) = content? else {
+            return Ok(());
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(values) => {
+                self.values = values;
+                info!("Settings loaded");
+            },
+            Err(error) => warn!("Settings reset to their defaults, could not be read: {}", error),
+        }
+
+        Ok(())
+    }
+
+    pub fn get<T: DeserializeOwned + Clone>(&self, setting: &Setting<T>) -> T {
+        self.get_value(setting.namespace, setting.key)
+            .unwrap_or_else(|| setting.default.clone())
+    }
+
+    pub fn set<T: Serialize>(&mut self, setting: &Setting<T>, value: T) -> anyhow::Result<()> {
+        self.set_value(setting.namespace, setting.key, &value)
+    }
+
+    /// `None` when unset, stored with another type, or owned by another app
+    pub fn get_value<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Option<T> {
+        if !self.can_read(namespace) {
+            warn!("Setting {}/{} of another app ignored", namespace, key);
+            return None;
+        }
+
+        let value = self.values.get(namespace)?.get(key)?;
+
+        match serde_json::from_value(value.clone()) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                warn!("Setting {}/{} ignored: {}", namespace, key, error);
+                None
+            },
+        }
+    }
+
+    /// Saved, then every app is told about it unless the value is the same, kept unchanged if it cannot be saved
+    pub fn set_value<T: Serialize>(&mut self, namespace: &str, key: &str, value: &T) -> anyhow::Result<()> {
+        self.check_write(namespace, key)?;
+
+        let value = serde_json::to_value(value)?;
+
+        if self.values.get(namespace).and_then(|namespace_values| namespace_values.get(key)) == Some(&value) {
+            return Ok(());
+        }
+
+        let previous = self.values.clone();
+        self.values.entry(String::from(namespace)).or_default().insert(String::from(key), value);
+        self.save_or_restore(previous)?;
+        self.changed(namespace, key);
+
+        Ok(())
+    }
+
+    /// Back to its default
+    pub fn reset<T>(&mut self, setting: &Setting<T>) -> anyhow::Result<()> {
+        self.check_write(setting.namespace, setting.key)?;
+
+        let previous = self.values.clone();
+        let removed = self.values
+            .get_mut(setting.namespace)
+            .and_then(|namespace_values| namespace_values.remove(setting.key));
+
+        if removed.is_some() {
+            self.save_or_restore(previous)?;
+            self.changed(setting.namespace, setting.key);
+        }
+
+        Ok(())
+    }
+
+    /// Every setting, to the SD card
+    pub fn export(&self, storage: &dyn Storage) -> anyhow::Result<()> {
+        self.check_every_namespace(false)?;
+
+        storage.write(Path::new(SETTINGS_EXPORT_PATH), serde_json::to_string_pretty(&self.values)?.as_bytes())?;
+        info!("Settings exported");
+        Ok(())
+    }
+
+    /// Replaces every setting with the SD card backup, `false` without one
+    pub fn import(&mut self, storage: &dyn Storage) -> anyhow::Result<bool> {
+        self.check_every_namespace(true)?;
+
+        let Some(content) = storage.read_to_string(Path::new(SETTINGS_EXPORT_PATH))? else {
+            return Ok(false);
+        };
+
+        let previous = std::mem::replace(&mut self.values, serde_json::from_str(&content)?);
+        self.save_or_restore(previous.clone())?;
+
+        let keys: BTreeSet<(&String, &String)> = previous
+            .iter()
+            .chain(self.values.iter())
+            .flat_map(|(namespace, namespace_values)| namespace_values.keys().map(move |key| (namespace, key)))
+            .collect();
+
+        for (namespace, key) in keys {
+            let before = previous.get(namespace).and_then(|namespace_values| namespace_values.get(key));
+            let after = self.values.get(namespace).and_then(|namespace_values| namespace_values.get(key));
+
+            if before != after {
+                self.changed(namespace, key);
+            }
+        }
+
+        info!("Settings imported");
+
+        Ok(true)
+    }
+
+    /// Goes back to the `previous` values if the store fails, so that memory and store agree
+    fn save_or_restore(&mut self, previous: Values) -> anyhow::Result<()> {
+        let Some(store) = self.store.as_mut() else {
+            return Ok(());
+        };
+
+        let saved = serde_json::to_string(&self.values)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| store.save(&content));
+
+        if saved.is_err() {
+            self.values = previous;
+        }
+
+        saved
+    }
+
+    fn changed(&self, namespace: &str, key: &str) {
+        self.events.setting_changed(SettingChange {
+            namespace: String::from(namespace),
+            key: String::from(key),
+        });
+    }
+}
+
+impl Phone {
+    /// Delivered to the app owning the namespace and to the core apps, the only ones which can read it
+    pub fn handle_setting_change(&mut self, change: &SettingChange) {
+        for index in 0..self.apps.len() {
+            let app_name = self.apps[index].app_name();
+
+            if self.is_disabled(index) || !(self.apps[index].is_core() || app_name == change.namespace) {
+                continue;
+            }
+
+            // A failing app does not keep the others from following the change
+            if let Err(error) = block_on(self.apps[index].on_setting_changed(&mut self.phone_data, change)) {
+                warn!("{} could not follow the change of {}: {}", app_name, change.key, error);
+            }
+        }
+    }
+}
@@ -1,2 +1,6 @@
+pub mod redraw;
+pub mod shade;
+// Kept as `ui::ui` rather than renamed, every screen imports its helpers from there
+#[allow(clippy::module_inception)]
 pub mod ui;
 pub mod widgets;
\ No newline at end of file
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// Decides when the screen must be redrawn, instead of redrawing it continuously
+#[derive(Debug)]
+pub struct RedrawScheduler {
+    dirty: bool,
+    /// Minute shown by the clock of the state bar
+    minute: Option<u64>,
+}
+
+impl Default for RedrawScheduler {
+    fn default() -> Self {
+        RedrawScheduler {
+            dirty: true,
+            minute: None,
+        }
+    }
+}
+
+impl RedrawScheduler {
+    /// Something on screen changed, e.g. by an app outside of its event handling
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// The clock is refreshed once a minute even if nothing else changed
+    pub fn should_redraw(&self, now: Duration) -> bool {
+        self.dirty || self.minute != Some(now.as_secs() / 60)
+    }
+
+    pub fn drawn(&mut self, now: Duration) {
+        self.dirty = false;
+        self.minute = Some(now.as_secs() / 60);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::apps::app::ClickableArea;
+use crate::events::{CoreEvent, EventType};
+use crate::notifications::{NotificationEvent, Priority};
+use crate::phone::Phone;
+use crate::ui::widgets::clickable_button::BorderedButton;
+use crate::ui::widgets::scrollable_list::ITEM_HEIGHT;
+use mousefood::prelude::{Color, Frame, Line, Rect, Stylize};
+use mousefood::ratatui::widgets::{Block, Paragraph};
+
+const DISMISS_WIDTH: u16 = 3;
+
+impl Phone {
+    /// Notification shade, pulled down from the state bar over the current screen, scrolled like the app list
+    pub fn render_shade(&mut self, frame: &mut Frame, area: Rect) -> anyhow::Result<EventType> {
+        let close_rect = Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: 1,
+        };
+
+        let inner_area = Rect {
+            x: area.x + 1,
+            y: area.y,
+            width: area.width - 2,
+            height: area.height,
+        };
+
+        let close = Line::raw("↑ Close").left_aligned().dark_gray();
+        frame.render_widget(close, close_rect);
+
+        let title = Line::raw("Notifications").bold().centered();
+        let title_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 2,
+            width: inner_area.width,
+            height: 1,
+        };
+        frame.render_widget(title, title_rect);
+
+        let mut events = vec![
+            ClickableArea(close_rect, Box::new(CoreEvent::Notification(NotificationEvent::CloseShade))),
+        ];
+
+        let notifications = self.phone_data.notifications.sorted();
+
+        if notifications.is_empty() {
+            let empty = Line::raw("No notifications").centered().dark_gray();
+            let empty_rect = Rect {
+                x: inner_area.x,
+                y: inner_area.y + 4,
+                width: inner_area.width,
+                height: 1,
+            };
+            frame.render_widget(empty, empty_rect);
+
+            return Ok(EventType::List(events));
+        }
+
+        // Leaves room for the clear button
+        let list_rect = Rect {
+            x: inner_area.x,
+            y: inner_area.y + 4,
+            width: inner_area.width,
+            height: inner_area.height.saturating_sub(4 + ITEM_HEIGHT),
+        };
+        let shown = notifications.len().min((list_rect.height / ITEM_HEIGHT) as usize);
+
+        events.extend(self.shade_list.render_with(frame, list_rect, notifications, |frame, notification, item_rect| {
+            let notification_rect = Rect {
+                x: item_rect.x,
+                y: item_rect.y,
+                width: item_rect.width.saturating_sub(DISMISS_WIDTH),
+                height: item_rect.height,
+            };
+
+            let dismiss_rect = Rect {
+                x: notification_rect.x + notification_rect.width,
+                y: notification_rect.y,
+                width: DISMISS_WIDTH,
+                height: item_rect.height,
+            };
+
+            let title_color = match notification.priority {
+                Priority::High => Color::Red,
+                Priority::Normal => Color::White,
+                Priority::Low => Color::Gray,
+            };
+
+            let block = Block::bordered()
+                .title(Line::raw(notification.title.as_str()).fg(title_color).bold())
+                .dark_gray();
+            let body = Paragraph::new(notification.body.as_str()).white().block(block);
+
+            frame.render_widget(body, notification_rect);
+            frame.render_widget(BorderedButton("x"), dismiss_rect);
+
+            vec![
+                ClickableArea(notification_rect, Box::new(CoreEvent::Notification(NotificationEvent::Act(notification.id)))),
+                ClickableArea(dismiss_rect, Box::new(CoreEvent::Notification(NotificationEvent::Dismiss(notification.id)))),
+            ]
+        }, |gesture| Box::new(CoreEvent::Notification(NotificationEvent::Scroll(gesture)))));
+
+        let clear_rect = Rect {
+            x: inner_area.x,
+            y: list_rect.y + (shown as u16 * ITEM_HEIGHT),
+            width: inner_area.width,
+            height: ITEM_HEIGHT,
+        };
+        frame.render_widget(BorderedButton("Clear all"), clear_rect);
+
+        events.push(ClickableArea(clear_rect, Box::new(CoreEvent::Notification(NotificationEvent::Clear))));
+
+        Ok(EventType::List(events))
+    }
+}
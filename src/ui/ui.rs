@@ -1,11 +1,15 @@
-use crate::events::EventType;
-use crate::phone::{Phone, WifiState};
+use crate::apps::app::ClickableArea;
+use crate::events::{CoreEvent, EventType};
+use crate::notifications::{NotificationEvent, Priority};
+use crate::phone::{Detection, Phone, TouchHealth, WifiState};
 use crate::state::PhoneState;
+use crate::ui::widgets::dialog::Dialog;
+use crate::ui::widgets::keyboard::KEYBOARD_HEIGHT;
+use log::warn;
 use mousefood::prelude::{Color, Frame, Line, Position, Rect, Span, Stylize};
 use mousefood::ratatui::widgets::{Block, Borders};
-use std::time::SystemTime;
 
-impl Phone<'_> {
+impl Phone {
     pub fn draw(&mut self, frame: &mut Frame) -> anyhow::Result<EventType> {
         let area = frame.area();
 
@@ -34,59 +38,99 @@ impl Phone<'_> {
 
         frame.render_widget(content_block, content_layout);
         
-        let events = match &self.state {
-            PhoneState::Homepage => self.render_app_list(frame, content_inner_layout),
-            PhoneState::InApp(index) => self.apps[*index].render(&mut self.phone_data, frame, content_inner_layout),
+        let events = match (self.shade_open, &self.state) {
+            (true, _) => self.render_shade(frame, content_inner_layout)?,
+            (false, PhoneState::Homepage) => self.render_app_list(frame, content_inner_layout)?,
+            (false, PhoneState::InApp(index)) => {
+                let app = &mut self.apps[*index];
+
+                match app.render(&mut self.phone_data, frame, content_inner_layout) {
+                    Ok(events) => events,
+                    // Shown to the user rather than retried forever, the app is left on OK
+                    Err(error) => {
+                        warn!("{} failed to render: {}", app.app_name(), error);
+
+                        let title = format!("{} failed", app.app_name());
+                        self.phone_data.show_dialog(
+                            Dialog::alert(&title, &error.to_string()).on_confirm(CoreEvent::GoBackToHomepage)
+                        );
+
+                        EventType::List(vec![])
+                    },
+                }
+            },
         };
 
-        if self.phone_data.keyboard.is_some() {
-            match events {
-                Ok(event_type) => match event_type {
-                    EventType::List(mut events) => {
-                        let keyboard_events = self.phone_data.keyboard.as_ref().unwrap().render(frame);
-                        events.extend(keyboard_events);
-                        Ok(EventType::List(events))
-                    }
-                    _ => Ok(event_type)
-                },
-                Err(_) => events
-            }
+        let EventType::List(mut events) = events;
+
+        // Before the app name, which it covers
+        if self.phone_data.notifications.unread().is_some() {
+            events.insert(0, ClickableArea(unread_rect(area), Box::new(CoreEvent::Notification(NotificationEvent::OpenShade))));
+        }
+
+        // Tapping the app name goes back
+        if self.shade_open || self.state != PhoneState::Homepage {
+            events.push(ClickableArea(state_bar_rect(area), Box::new(CoreEvent::Back)));
         }
-        else {
-            events
+
+        let mut bottom = area.y + area.height;
+
+        if let Some(keyboard) = self.phone_data.keyboard.as_ref().filter(|_| !self.shade_open) {
+            events.extend(keyboard.render(frame));
+            bottom = bottom.saturating_sub(KEYBOARD_HEIGHT);
+        }
+
+        // Over the screen, so it is tapped first
+        if let Some(toast) = &self.phone_data.toast {
+            events.insert(0, toast.render(frame, bottom));
         }
+
+        // Modal, nothing else can be tapped
+        if let Some(dialog) = self.phone_data.dialogs.first() {
+            events = dialog.render(frame);
+        }
+
+        Ok(EventType::List(events))
     }
 
     pub fn render_state_bar(&self, frame: &mut Frame) {
-        let state_rect = Rect {
-            x: 0,
-            y: 0,
-            width: 17,
-            height: 1,
-        };
+        let area = frame.area();
+        let time_x = area.x + (area.width - 5) / 2;
+        let state_rect = state_bar_rect(area);
 
         let time_rect = Rect {
-            x: 17,
-            y: 0,
+            x: time_x,
+            y: area.y,
             width: 5,
             height: 1,
         };
 
         let wifi_rect = Rect {
-            x: 22,
-            y: 0,
-            width: 18,
+            x: time_x + 5,
+            y: area.y,
+            width: area.x + area.width - (time_x + 5),
             height: 1,
         };
 
         let state_text = match self.state {
-            PhoneState::Homepage => "PhoneOS",
-            PhoneState::InApp(index) => self.apps[index].app_name(),
+            PhoneState::Homepage => match self.safe_mode {
+                true => String::from("Safe mode"),
+                false => String::from("PhoneOS"),
+            },
+            PhoneState::InApp(index) => format!("← {}", self.apps[index].app_name()),
         };
         
-        let state_line = Line::raw(state_text).left_aligned();
+        // Touch problems take precedence, as the user may wonder why the phone stopped responding
+        let state_line = match self.phone_data.touch_health {
+            TouchHealth::Healthy => match (self.state, self.phone_data.sd_card) {
+                (PhoneState::Homepage, Detection::Missing) if !self.safe_mode => Line::raw("No SD card").left_aligned().yellow(),
+                _ => Line::raw(state_text).left_aligned(),
+            },
+            TouchHealth::Degraded => Line::raw("Touch degraded").left_aligned().yellow(),
+            TouchHealth::Unavailable => Line::raw("Touch unavailable").left_aligned().red(),
+        };
 
-        let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+        let time = self.phone_data.time.now();
         let seconds = time.as_secs();
         let minutes = seconds / 60;
         let hours = minutes / 60;
@@ -94,6 +138,7 @@ impl Phone<'_> {
         let time_line = Line::raw(time_string).centered().dark_gray();
 
         let (wifi_state_text, color) = match &self.phone_data.wifi_state {
+            WifiState::Unavailable => ("No WiFi", Color::DarkGray),
             WifiState::NotInitialized => ("Not initialized", Color::Red),
             WifiState::NotConnected => ("Not connected", Color::Red),
             WifiState::Connecting => ("Connecting", Color::Yellow),
@@ -103,6 +148,21 @@ impl Phone<'_> {
         let wifi_line = Line::raw(wifi_state_text).right_aligned().fg(color);
 
         frame.render_widget(state_line, state_rect);
+
+        if let Some((count, priority)) = self.phone_data.notifications.unread() {
+            // Spaced from the clock
+            let text = match count > 9 {
+                true => String::from("●9+ "),
+                false => format!("●{} ", count),
+            };
+
+            let indicator_color = match priority {
+                Priority::High => Color::Red,
+                _ => Color::Yellow,
+            };
+
+            frame.render_widget(Line::raw(text).right_aligned().fg(indicator_color), unread_rect(area));
+        }
         frame.render_widget(time_line, time_rect);
         frame.render_widget(wifi_line, wifi_rect);
     }
@@ -116,6 +176,30 @@ impl Phone<'_> {
     }
 }
 
+/// Left of the clock, showing the phone state
+fn state_bar_rect(area: Rect) -> Rect {
+    let time_x = area.x + (area.width - 5) / 2;
+
+    Rect {
+        x: area.x,
+        y: area.y,
+        width: time_x - area.x,
+        height: 1,
+    }
+}
+
+/// End of the state, just before the clock
+fn unread_rect(area: Rect) -> Rect {
+    let state_rect = state_bar_rect(area);
+
+    Rect {
+        x: state_rect.x + state_rect.width.saturating_sub(5),
+        y: area.y,
+        width: 5.min(state_rect.width),
+        height: 1,
+    }
+}
+
 /*
 _____  _
 |  __ \| |
@@ -128,4 +212,4 @@ _____  _
 | |  | |\___ \
 | |__| |____) |
 \____/|_____/
-*/
\ No newline at end of file
+*/
@@ -0,0 +1,230 @@
+use mousefood::prelude::{Frame, Line, Rect, Stylize};
+use mousefood::ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use crate::apps::app::ClickableArea;
+use crate::events::{AppEvent, CoreEvent};
+use crate::phone::{Phone, PhoneData};
+use crate::runtime::block_on;
+use crate::state::PhoneState;
+use crate::ui::widgets::clickable_button::BorderedButton;
+
+const BUTTON_HEIGHT: u16 = 3;
+/// Longer messages are cut
+const MAX_MESSAGE_LINES: u16 = 8;
+
+#[derive(Debug)]
+pub enum DialogEvent {
+    Confirm,
+    Cancel,
+}
+
+/// Modal box over the current screen, only its buttons can be tapped.
+/// The event of the tapped button is posted back to the app which showed the dialog, see `Phone::handle_dialog_event`.
+#[derive(Debug)]
+pub struct Dialog {
+    pub title: String,
+    pub message: String,
+    pub confirm_label: String,
+    pub on_confirm: Option<Box<dyn AppEvent>>,
+    /// Without it, the dialog only has the confirm button
+    pub cancel_label: Option<String>,
+    pub on_cancel: Option<Box<dyn AppEvent>>,
+    /// Set by `PhoneData::show_dialog`, none when shown by the phone itself
+    owner: Option<&'static str>,
+}
+
+impl Dialog {
+    /// Single OK button
+    pub fn alert(title: &str, message: &str) -> Self {
+        Dialog {
+            title: String::from(title),
+            message: String::from(message),
+            confirm_label: String::from("OK"),
+            on_confirm: None,
+            cancel_label: None,
+            on_cancel: None,
+            owner: None,
+        }
+    }
+
+    /// OK and Cancel buttons
+    pub fn confirm<E: AppEvent>(title: &str, message: &str, on_confirm: E) -> Self {
+        Dialog {
+            title: String::from(title),
+            message: String::from(message),
+            confirm_label: String::from("OK"),
+            on_confirm: Some(Box::new(on_confirm)),
+            cancel_label: Some(String::from("Cancel")),
+            on_cancel: None,
+            owner: None,
+        }
+    }
+
+    pub fn on_confirm<E: AppEvent>(mut self, event: E) -> Self {
+        self.on_confirm = Some(Box::new(event));
+        self
+    }
+
+    pub fn on_cancel<E: AppEvent>(mut self, event: E) -> Self {
+        self.on_cancel = Some(Box::new(event));
+        self
+    }
+
+    /// Returns the only clickable areas while the dialog is shown
+    pub fn render(&self, frame: &mut Frame) -> Vec<ClickableArea> {
+        let screen = frame.area();
+        let width = screen.width.saturating_sub(4);
+
+        let message = Paragraph::new(self.message.as_str()).wrap(Wrap { trim: true });
+        let message_lines = wrapped_lines(&self.message, width.saturating_sub(2)).clamp(1, MAX_MESSAGE_LINES);
+
+        // Borders, message, spacing and buttons
+        let height = 2 + message_lines + 1 + BUTTON_HEIGHT;
+
+        let area = Rect {
+            x: screen.x + 2,
+            y: screen.y + screen.height.saturating_sub(height) / 2,
+            width,
+            height: height.min(screen.height),
+        };
+
+        let block = Block::bordered().title(Line::raw(self.title.as_str()).bold().centered());
+        let inner = block.inner(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let message_rect = Rect {
+            x: inner.x,
+            y: inner.y,
+            width: inner.width,
+            height: message_lines,
+        };
+        frame.render_widget(message, message_rect);
+
+        let buttons_rect = Rect {
+            x: inner.x,
+            y: inner.y + message_lines + 1,
+            width: inner.width,
+            height: BUTTON_HEIGHT,
+        };
+
+        match &self.cancel_label {
+            None => {
+                frame.render_widget(BorderedButton(&self.confirm_label), buttons_rect);
+
+                vec![
+                    ClickableArea(buttons_rect, Box::new(CoreEvent::Dialog(DialogEvent::Confirm))),
+                ]
+            },
+            Some(cancel_label) => {
+                let cancel_rect = Rect {
+                    x: buttons_rect.x,
+                    y: buttons_rect.y,
+                    width: buttons_rect.width / 2,
+                    height: BUTTON_HEIGHT,
+                };
+
+                let confirm_rect = Rect {
+                    x: buttons_rect.x + cancel_rect.width,
+                    y: buttons_rect.y,
+                    width: buttons_rect.width - cancel_rect.width,
+                    height: BUTTON_HEIGHT,
+                };
+
+                frame.render_widget(BorderedButton(cancel_label), cancel_rect);
+                frame.render_widget(BorderedButton(&self.confirm_label), confirm_rect);
+
+                vec![
+                    ClickableArea(cancel_rect, Box::new(CoreEvent::Dialog(DialogEvent::Cancel))),
+                    ClickableArea(confirm_rect, Box::new(CoreEvent::Dialog(DialogEvent::Confirm))),
+                ]
+            },
+        }
+    }
+}
+
+/// Lines taken by the text once wrapped at word boundaries
+fn wrapped_lines(text: &str, width: u16) -> u16 {
+    let width = width.max(1) as usize;
+    let mut lines = 0;
+
+    for paragraph in text.lines() {
+        let mut line_length = 0;
+        lines += 1;
+
+        for word in paragraph.split_whitespace() {
+            let word_length = word.chars().count();
+
+            if line_length > 0 && line_length + 1 + word_length > width {
+                lines += 1;
+                line_length = 0;
+            }
+
+            // Words longer than the line are split
+            lines += (word_length.saturating_sub(1) / width) as u16;
+            line_length = match line_length {
+                0 => word_length % width,
+                _ => line_length + 1 + word_length,
+            };
+        }
+    }
+
+    lines
+}
+
+impl PhoneData {
+    /// Queued after the dialogs already shown, unless the same one is already waiting.
+    /// Owned by the app being called, which alone gets the event of the tapped button.
+    pub fn show_dialog(&mut self, mut dialog: Dialog) {
+        dialog.owner = self.services.bound_app();
+
+        let duplicate = self.dialogs
+            .iter()
+            .any(|shown| shown.title == dialog.title && shown.message == dialog.message);
+
+        if !duplicate {
+            self.dialogs.push(dialog);
+            self.redraw.mark_dirty();
+        }
+    }
+}
+
+impl Phone {
+    /// Closes the dialog on top, then posts the event of the tapped button to the app which showed it
+    pub fn handle_dialog_event(&mut self, event: &DialogEvent) -> anyhow::Result<Option<PhoneState>> {
+        if self.phone_data.dialogs.is_empty() {
+            return Ok(None);
+        }
+
+        let dialog = self.phone_data.dialogs.remove(0);
+
+        let action = match event {
+            DialogEvent::Confirm => dialog.on_confirm,
+            DialogEvent::Cancel => dialog.on_cancel,
+        };
+
+        let Some(action) = action else {
+            return Ok(None);
+        };
+
+        // E.g. back to the homepage, or shown by the phone
+        if action.as_ref().as_any().is::<CoreEvent>() || dialog.owner.is_none() {
+            return self.handle_posted_event(action.as_ref());
+        }
+
+        // Uninstalled or disabled since
+        let Some(index) = (0..self.apps.len())
+            .find(|index| Some(self.apps[*index].app_name()) == dialog.owner && !self.is_disabled(*index))
+        else {
+            return Ok(None);
+        };
+
+        let state = block_on(self.apps[index].handle_event(&mut self.phone_data, action.as_ref()))?;
+        self.collect_screens(index);
+
+        Ok(match self.state == PhoneState::InApp(index) {
+            true => state,
+            false => None,
+        })
+    }
+}
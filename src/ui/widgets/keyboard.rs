@@ -1,5 +1,4 @@
 use crate::apps::app::{ClickableArea};
-use crate::drivers::ili9341::{HEIGHT, WIDTH};
 use crate::phone::PhoneData;
 use crate::ui::widgets::keyboard::KeyboardEvent::*;
 use mousefood::prelude::{Color, Frame, Rect, Stylize};
@@ -7,32 +6,36 @@ use mousefood::ratatui::widgets::{Block, Paragraph};
 use once_cell::sync::Lazy;
 
 pub const KEYBOARD_HEIGHT: u16 = 12;
+pub const KEYBOARD_WIDTH: u16 = 40;
 
-static AZERTY: Lazy<[Vec<(&'static str, KeyboardEvent, u16)>;4]> = Lazy::new(|| [
+/// Rows of keys, each with its label, event and column
+type KeyRows = [Vec<(&'static str, KeyboardEvent, u16)>; 4];
+
+static AZERTY: Lazy<KeyRows> = Lazy::new(|| [
     vec![("a", Letter('a'), 0), ("z", Letter('z'), 3), ("e", Letter('e'), 6), ("r", Letter('r'), 9), ("t", Letter('t'), 12), ("y", Letter('y'), 15), ("u", Letter('u'), 18), ("i", Letter('i'), 21), ("o", Letter('o'), 24), ("p", Letter('p'), 27)],
     vec![("q", Letter('q'), 0), ("s", Letter('s'), 3), ("d", Letter('d'), 6), ("f", Letter('f'), 9), ("g", Letter('g'), 12), ("h", Letter('h'), 15), ("j", Letter('j'), 18), ("k", Letter('k'), 21), ("l", Letter('l'), 24), ("m", Letter('m'), 27)],
     vec![("Maj", Maj, 0), ("w", Letter('w'), 5), ("x", Letter('x'), 8), ("c", Letter('c'), 11), ("v", Letter('v'), 14), ("b", Letter('b'), 17), ("n", Letter('n'), 20), ("'", Letter('\''), 23), ("DEL", Delete, 26)],
     vec![("?123", Symbols(SymbolLevel::First), 0), (",", Letter(','), 6), ("          ", Letter('x'), 9), (".", Letter('.'), 21), ("ENTER", Enter, 24)],
 ]);
 
-static SYMBOLS_1: Lazy<[Vec<(&'static str, KeyboardEvent, u16)>;4]> = Lazy::new(|| [
+static SYMBOLS_1: Lazy<KeyRows> = Lazy::new(|| [
     vec![("1", Letter('1'), 0), ("2", Letter('2'), 3), ("3", Letter('3'), 6), ("4", Letter('4'), 9), ("5", Letter('5'), 12), ("6", Letter('6'), 15), ("7", Letter('7'), 18), ("8", Letter('8'), 21), ("9", Letter('9'), 24), ("0", Letter('0'), 27)],
     vec![("@", Letter('@'), 0), ("#", Letter('#'), 3), ("€", Letter('€'), 6), ("_", Letter('_'), 9), ("&", Letter('&'), 12), ("-", Letter('-'), 15), ("+", Letter('+'), 18), ("(", Letter('('), 21), (")", Letter(')'), 24), ("/", Letter('/'), 27)],
     vec![("=\\<", Symbols(SymbolLevel::Second), 0), ("*", Letter('*'), 5), ("\"", Letter('"'), 8), ("'", Letter('\''), 11), (":", Letter(':'), 14), (";", Letter(';'), 17), ("!", Letter('!'), 20), ("?", Letter('?'), 23), ("DEL", Delete, 26)],
     vec![("?123", Symbols(SymbolLevel::None), 0), (",", Letter(','), 6), ("          ", Letter('x'), 9), (".", Letter('.'), 21), ("ENTER", Enter, 24)],
 ]);
 
-static SYMBOLS_2: Lazy<[Vec<(&'static str, KeyboardEvent, u16)>;4]> = Lazy::new(|| [
+static SYMBOLS_2: Lazy<KeyRows> = Lazy::new(|| [
     vec![("~", Letter('~'), 0), ("`", Letter('`'), 3), ("|", Letter('|'), 6), ("•", Letter('•'), 9), ("√", Letter('√'), 12), ("π", Letter('π'), 15), ("÷", Letter('÷'), 18), ("×", Letter('×'), 21), ("§", Letter('§'), 24), ("∆", Letter('∆'), 27)],
     vec![("£", Letter('£'), 0), ("¥", Letter('¥'), 3), ("$", Letter('$'), 6), ("¢", Letter('¢'), 9), ("^", Letter('^'), 12), ("°", Letter('°'), 15), ("=", Letter('='), 18), ("{", Letter('{'), 21), ("}", Letter('}'), 24), ("\\", Letter('\\'), 27)],
     vec![("=\\<", Symbols(SymbolLevel::First), 0), ("%", Letter('%'), 5), ("©", Letter('©'), 8), ("®", Letter('®'), 11), ("™", Letter('™'), 14), ("✓", Letter('✓'), 17), ("[", Letter('['), 20), ("]", Letter(']'), 23), ("DEL", Delete, 26)],
     vec![("?123", Symbols(SymbolLevel::None), 0), ("<", Letter('<'), 6), ("          ", Letter('x'), 9), (">", Letter('>'), 21), ("ENTER", Enter, 24)],
 ]);
 
-static AZERTY_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&*AZERTY, false));
-static AZERTY_MAJ_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&*AZERTY, true));
-static SYMBOLS_1_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&*SYMBOLS_1, false));
-static SYMBOLS_2_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&*SYMBOLS_2, false));
+static AZERTY_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&AZERTY, false));
+static AZERTY_MAJ_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&AZERTY, true));
+static SYMBOLS_1_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&SYMBOLS_1, false));
+static SYMBOLS_2_EVENTS: Lazy<Vec<PreRenderedParagraph>> = Lazy::new(|| prerender_layout(&SYMBOLS_2, false));
 
 struct PreRenderedParagraph<'a> {
     pub paragraph: Paragraph<'a>,
@@ -81,9 +84,14 @@ impl KeyboardEvent {
 }
 
 impl Keyboard {
+    /// Renders at the bottom of the screen, horizontally centered
     pub fn render(&self, frame: &mut Frame) -> Vec<ClickableArea> {
         let mut events = Vec::new();
 
+        let screen = frame.area();
+        let offset_x = screen.x + screen.width.saturating_sub(KEYBOARD_WIDTH) / 2;
+        let offset_y = screen.y + screen.height.saturating_sub(KEYBOARD_HEIGHT);
+
         let pre_rendered_keyboard = match self.symbols {
             SymbolLevel::None => match self.layout {
                 KeyboardLayout::Azerty => match self.maj {
@@ -101,9 +109,16 @@ impl Keyboard {
                 false => (pre_rendered_paragraph.paragraph.clone(), pre_rendered_paragraph.event.clone())
             };
 
-            frame.render_widget(paragraph, pre_rendered_paragraph.area);
+            let area = Rect {
+                x: offset_x + pre_rendered_paragraph.area.x,
+                y: offset_y + pre_rendered_paragraph.area.y,
+                width: pre_rendered_paragraph.area.width,
+                height: pre_rendered_paragraph.area.height,
+            };
+
+            frame.render_widget(paragraph, area);
 
-            events.push(ClickableArea(pre_rendered_paragraph.area, event));
+            events.push(ClickableArea(area, event));
         }
 
         events
@@ -142,11 +157,12 @@ impl PhoneData {
     }
 }
 
-fn prerender_layout(keyboard_layout: &[Vec<(&'static str, KeyboardEvent, u16)>; 4], uppercase: bool) -> Vec<PreRenderedParagraph<'static>> {
+/// Key areas are relative to the top left corner of the keyboard
+fn prerender_layout(keyboard_layout: &KeyRows, uppercase: bool) -> Vec<PreRenderedParagraph<'static>> {
     let area = Rect {
         x: 0,
-        y: HEIGHT - KEYBOARD_HEIGHT,
-        width: WIDTH,
+        y: 0,
+        width: KEYBOARD_WIDTH,
         height: KEYBOARD_HEIGHT,
     };
 
@@ -156,7 +172,7 @@ fn prerender_layout(keyboard_layout: &[Vec<(&'static str, KeyboardEvent, u16)>;
         for (text, event, x) in row {
             let (text, event) = match uppercase {
                 true => (text.to_ascii_uppercase(), event.maj_letter()),
-                false => (text.to_string(), event.clone())
+                false => (text.to_string(), *event)
             };
 
             let rect = Rect {
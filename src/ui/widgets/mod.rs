@@ -1,2 +1,5 @@
 pub mod clickable_button;
-pub mod keyboard;
\ No newline at end of file
+pub mod dialog;
+pub mod keyboard;
+pub mod scrollable_list;
+pub mod toast;
@@ -0,0 +1,137 @@
+use mousefood::prelude::{Frame, Position, Rect};
+use mousefood::ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState};
+use crate::apps::app::ClickableArea;
+use crate::events::AppEvent;
+use crate::events::gestures::{Gesture, GestureKind, SwipeDirection};
+use crate::ui::widgets::clickable_button::BorderedButton;
+
+pub const ITEM_HEIGHT: u16 = 3;
+
+/// Vertical list of bordered buttons, scrolled by dragging or swiping over it
+#[derive(Debug, Default)]
+pub struct ScrollableList {
+    /// Index of the first visible item
+    offset: usize,
+    /// Dragged rows not yet amounting to a whole item
+    drag_remainder: i16,
+    /// Number of fully visible items during the last render
+    visible_items: usize,
+    /// Drag of the current touch, whose ending `Swipe` is ignored once it scrolled
+    drag: Option<DragTrack>,
+}
+
+#[derive(Debug)]
+struct DragTrack {
+    /// Where the finger went down, also the origin of the `Swipe`
+    origin: Position,
+    last: Position,
+    scrolled: bool,
+}
+
+impl ScrollableList {
+    /// Renders the visible items and returns their clickable areas, along with the scroll subscriptions
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, items: Vec<(&str, Box<dyn AppEvent>)>, on_scroll: fn(Gesture) -> Box<dyn AppEvent>) -> Vec<ClickableArea> {
+        self.render_with(frame, area, items, |frame, (label, event), item_rect| {
+            frame.render_widget(BorderedButton(label), item_rect);
+            vec![ClickableArea(item_rect, event)]
+        }, on_scroll)
+    }
+
+    /// Same as `render`, with items drawn by `render_item` in their `ITEM_HEIGHT` rows
+    pub fn render_with<T>(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        items: Vec<T>,
+        mut render_item: impl FnMut(&mut Frame, T, Rect) -> Vec<ClickableArea>,
+        on_scroll: fn(Gesture) -> Box<dyn AppEvent>,
+    ) -> Vec<ClickableArea> {
+        let mut events = Vec::new();
+
+        self.visible_items = (area.height / ITEM_HEIGHT) as usize;
+
+        let max_offset = items.len().saturating_sub(self.visible_items);
+        self.offset = self.offset.min(max_offset);
+
+        let overflows = max_offset > 0;
+
+        let items_width = match overflows {
+            true => area.width.saturating_sub(1),
+            false => area.width,
+        };
+
+        for (index, item) in items.into_iter().skip(self.offset).take(self.visible_items).enumerate() {
+            let item_rect = Rect {
+                x: area.x,
+                y: area.y + (index as u16 * ITEM_HEIGHT),
+                width: items_width,
+                height: ITEM_HEIGHT,
+            };
+
+            events.extend(render_item(frame, item, item_rect));
+        }
+
+        if overflows {
+            let mut scrollbar_state = ScrollbarState::new(max_offset + 1).position(self.offset);
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None);
+
+            frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+        }
+
+        events.push(ClickableArea::gesture(area, GestureKind::Drag, on_scroll));
+        events.push(ClickableArea::gesture(area, GestureKind::Swipe, on_scroll));
+
+        events
+    }
+
+    pub fn handle_gesture(&mut self, gesture: &Gesture) {
+        match gesture {
+            // Dragging the finger up scrolls down
+            Gesture::Drag { from, to } => {
+                // A new touch starts away from where the last drag ended
+                if self.drag.as_ref().map_or(true, |drag| drag.last != *from) {
+                    self.drag_remainder = 0;
+                    self.drag = Some(DragTrack { origin: *from, last: *from, scrolled: false });
+                }
+
+                self.drag_remainder += from.y as i16 - to.y as i16;
+
+                let items = self.drag_remainder / ITEM_HEIGHT as i16;
+                self.drag_remainder %= ITEM_HEIGHT as i16;
+
+                if let Some(drag) = self.drag.as_mut() {
+                    drag.last = *to;
+                    drag.scrolled |= items != 0;
+                }
+
+                self.scroll_by(items as isize);
+            },
+            Gesture::Swipe { from, direction } => {
+                let drag = self.drag.take();
+
+                // Ends a touch whose drag already scrolled the list
+                if drag.is_some_and(|drag| drag.scrolled && drag.origin == *from) {
+                    return;
+                }
+
+                match direction {
+                    SwipeDirection::Up => self.scroll_by(self.visible_items as isize),
+                    SwipeDirection::Down => self.scroll_by(-(self.visible_items as isize)),
+                    _ => {}
+                }
+            },
+            _ => {}
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Upper bound is enforced on the next render, when the item count is known
+    fn scroll_by(&mut self, items: isize) {
+        self.offset = self.offset.saturating_add_signed(items);
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+use mousefood::prelude::{Color, Frame, Line, Rect, Stylize};
+use mousefood::ratatui::widgets::{Block, Clear, Paragraph};
+use crate::apps::app::ClickableArea;
+use crate::events::CoreEvent;
+use crate::phone::PhoneData;
+
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+const TOAST_HEIGHT: u16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Error,
+}
+
+/// Short message over the bottom of the screen, going away on its own or when tapped
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub level: ToastLevel,
+    pub expires: Instant,
+}
+
+impl Toast {
+    pub fn info(text: &str) -> Self {
+        Toast {
+            text: String::from(text),
+            level: ToastLevel::Info,
+            expires: Instant::now() + TOAST_DURATION,
+        }
+    }
+
+    pub fn error(text: &str) -> Self {
+        Toast {
+            text: String::from(text),
+            level: ToastLevel::Error,
+            expires: Instant::now() + TOAST_DURATION,
+        }
+    }
+
+    /// Above `bottom`, e.g. the keyboard top
+    pub fn render(&self, frame: &mut Frame, bottom: u16) -> ClickableArea {
+        let screen = frame.area();
+
+        let area = Rect {
+            x: screen.x + 1,
+            y: bottom.saturating_sub(TOAST_HEIGHT + 1),
+            width: screen.width.saturating_sub(2),
+            height: TOAST_HEIGHT,
+        };
+
+        let color = match self.level {
+            ToastLevel::Info => Color::White,
+            ToastLevel::Error => Color::Red,
+        };
+
+        let toast = Paragraph::new(Line::raw(self.text.as_str()).centered())
+            .fg(color)
+            .block(Block::bordered().fg(color));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(toast, area);
+
+        ClickableArea(area, Box::new(CoreEvent::DismissToast))
+    }
+}
+
+impl PhoneData {
+    /// Replaces the current toast, if any
+    pub fn show_toast(&mut self, toast: Toast) {
+        self.toast = Some(toast);
+        self.redraw.mark_dirty();
+    }
+}
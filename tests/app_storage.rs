@@ -0,0 +1,203 @@
+use std::path::Path;
+use std::sync::Barrier;
+use std::thread;
+use esp32_phone_os::app_storage::APPS_DIRECTORY;
+use esp32_phone_os::apps::storage::StorageEvent;
+use esp32_phone_os::events::CoreEvent;
+use esp32_phone_os::platform::Storage;
+use esp32_phone_os::state::PhoneState;
+use esp32_phone_os::ui::widgets::dialog::DialogEvent;
+use common::{phone, TempStorage};
+
+mod common;
+
+#[test]
+fn each_app_gets_its_own_directory() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    for app_id in ["wifi_settings", "touch_calibration", "display_settings", "storage_settings"] {
+        assert!(card.root().join(APPS_DIRECTORY).join(app_id).is_dir());
+    }
+
+    // The WiFi app saved its defaults in its own directory
+    let wifi_usage = phone.phone_data.app_usage.lock().unwrap()["WiFi settings"];
+    assert!(wifi_usage > 0);
+    assert!(!card.root().join("wifis.dat").exists());
+}
+
+#[test]
+fn paths_outside_of_the_app_directory_are_rejected() {
+    let card = TempStorage::new();
+    card.write(Path::new("secret.txt"), b"PIN 1234").unwrap();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    let storage = phone.phone_data.app_storage("Display settings").unwrap();
+
+    for path in ["../../secret.txt", "/secret.txt", "notes/../../../secret.txt", ""] {
+        assert!(storage.read_to_string(Path::new(path)).is_err(), "{} was read", path);
+        assert!(storage.write(Path::new(path), b"").is_err(), "{} was written", path);
+        assert!(storage.remove(Path::new(path)).is_err(), "{} was removed", path);
+        assert!(storage.create_dir(Path::new(path)).is_err(), "{} was created", path);
+    }
+
+    storage.write(Path::new("./notes.txt"), b"Hello").unwrap();
+    assert_eq!(storage.read_to_string(Path::new("notes.txt")).unwrap().unwrap(), "Hello");
+    assert!(card.root().join("apps/display_settings/notes.txt").exists());
+}
+
+#[test]
+fn apps_stay_within_their_quota() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    let mut storage = phone.phone_data.app_storage("Display settings").unwrap();
+    storage.quota = 10;
+
+    storage.write(Path::new("a.txt"), b"12345").unwrap();
+    storage.append(Path::new("a.txt"), b"678").unwrap();
+    assert_eq!(storage.usage(), 8);
+
+    assert!(storage.write(Path::new("b.txt"), b"123").is_err());
+    assert!(!card.root().join("apps/display_settings/b.txt").exists());
+
+    // Replacing a file only counts the difference
+    storage.write(Path::new("a.txt"), b"1234567890").unwrap();
+    assert_eq!(storage.usage(), 10);
+
+    storage.remove(Path::new("a.txt")).unwrap();
+    assert_eq!(storage.usage(), 0);
+
+    // Measured again on the next boot
+    storage.write(Path::new("c.txt"), b"123").unwrap();
+
+    let mut rebooted = common::phone();
+    rebooted.phone_data.storage = Some(Box::new(card));
+    rebooted.init().unwrap();
+    assert_eq!(rebooted.phone_data.app_storage("Display settings").unwrap().usage(), 3);
+}
+
+#[test]
+fn failed_writes_do_not_use_the_quota() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    let storage = phone.phone_data.app_storage("Display settings").unwrap();
+
+    assert!(storage.write(Path::new("notes/today.txt"), b"Hello").is_err());
+    assert!(storage.append(Path::new("notes/today.txt"), b"Hello").is_err());
+    assert_eq!(storage.usage(), 0);
+
+    storage.create_dir(Path::new("notes")).unwrap();
+    storage.write(Path::new("notes/today.txt"), b"Hello").unwrap();
+    assert_eq!(storage.usage(), 5);
+    assert!(card.root().join("apps/display_settings/notes/today.txt").exists());
+}
+
+#[test]
+fn concurrent_writes_stay_within_the_quota() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    for round in 0..20 {
+        let barrier = Barrier::new(2);
+
+        let written = thread::scope(|scope| {
+            let writers = ["a", "b"].map(|name| {
+                let mut storage = phone.phone_data.app_storage("Display settings").unwrap();
+                storage.quota = 10;
+                let barrier = &barrier;
+
+                scope.spawn(move || {
+                    barrier.wait();
+                    storage.write(Path::new(&format!("{}{}.txt", name, round)), b"123456").is_ok()
+                })
+            });
+
+            writers.map(|writer| writer.join().unwrap())
+        });
+
+        assert_eq!(written.iter().filter(|written| **written).count(), 1);
+
+        let storage = phone.phone_data.app_storage("Display settings").unwrap();
+        assert_eq!(storage.usage(), 6);
+
+        for name in ["a", "b"] {
+            storage.remove(Path::new(&format!("{}{}.txt", name, round))).unwrap();
+        }
+    }
+}
+
+#[test]
+fn moved_files_stay_within_the_quota() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    card.write(Path::new("notes.txt"), b"12345").unwrap();
+
+    let mut storage = phone.phone_data.app_storage("Display settings").unwrap();
+    storage.quota = 4;
+
+    assert!(storage.adopt(&card, Path::new("notes.txt")).is_err());
+    assert!(card.root().join("notes.txt").exists());
+    assert_eq!(storage.usage(), 0);
+
+    storage.quota = 5;
+    storage.adopt(&card, Path::new("notes.txt")).unwrap();
+    assert_eq!(storage.usage(), 5);
+}
+
+#[test]
+fn known_wifis_of_older_versions_are_moved() {
+    let card = TempStorage::new();
+    card.write(Path::new("wifis.dat"), b"[]").unwrap();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+
+    assert!(!card.root().join("wifis.dat").exists());
+    assert_eq!(card.read_to_string(Path::new("apps/wifi_settings/wifis.dat")).unwrap().unwrap(), "[]");
+}
+
+#[test]
+fn app_data_is_cleared_from_the_storage_settings() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+    phone.phone_data.app_storage("Display settings").unwrap().write(Path::new("notes.txt"), b"Hello").unwrap();
+
+    phone.set_state(PhoneState::InApp(3)).unwrap();
+    phone.dispatch_event(&StorageEvent::AskClear("WiFi settings")).unwrap();
+    assert_eq!(phone.phone_data.dialogs[0].title, "Clear WiFi settings data?");
+
+    phone.dispatch_event(&CoreEvent::Dialog(DialogEvent::Confirm)).unwrap();
+
+    assert_eq!(phone.phone_data.app_storage("WiFi settings").unwrap().usage(), 0);
+    assert!(!card.root().join("apps/wifi_settings/wifis.dat").exists());
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "WiFi settings data cleared");
+
+    // The other apps keep theirs
+    assert_eq!(phone.phone_data.app_storage("Display settings").unwrap().usage(), 5);
+    assert_eq!(phone.state, PhoneState::InApp(3));
+}
@@ -0,0 +1,117 @@
+use esp32_phone_os::apps::app::AppHandler;
+use esp32_phone_os::apps::calibration::{target_pixel, targets, CalibrationApp, CalibrationAppState};
+use esp32_phone_os::drivers::display::{DisplayModel, Screen, ScreenOrientation};
+use esp32_phone_os::drivers::touch::{TouchEvent, TouchPoint};
+use esp32_phone_os::events::calibration::TouchCalibration;
+use esp32_phone_os::platform::recording::TouchRecord;
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Position, Rect, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use std::sync::{Arc, Mutex};
+use common::{phone, MockWifi, HEIGHT, WIDTH};
+
+mod common;
+
+/// Raw point reported by a panel with swapped axes and a 10x resolution
+fn swapped_panel(x: f32, y: f32) -> TouchPoint {
+    TouchPoint {
+        x: (100.0 + y * 10.0) as u16,
+        y: (50.0 + x * 10.0) as u16,
+        id: 0,
+        event: Some(TouchEvent::Press),
+    }
+}
+
+/// Press, hold, then release a raw point
+fn tap(records: &mut Vec<TouchRecord>, timestamp_ms: u64, touch: TouchPoint) {
+    records.push(TouchRecord { timestamp_ms, touches: vec![touch] });
+    records.push(TouchRecord { timestamp_ms: timestamp_ms + 20, touches: vec![TouchPoint { event: Some(TouchEvent::Move), ..touch }] });
+    records.push(TouchRecord { timestamp_ms: timestamp_ms + 80, touches: vec![] });
+}
+
+/// Raw point of the default FT6206 panel
+fn default_panel(cell: Position) -> TouchPoint {
+    TouchPoint {
+        x: 240 - cell.x * 6,
+        y: 319 - (cell.y * 10 + 5),
+        id: 0,
+        event: Some(TouchEvent::Press),
+    }
+}
+
+#[test]
+fn solves_swapped_axes() {
+    let raw = [(0.0, 0.0), (100.0, 0.0), (0.0, 200.0)].map(|(x, y)| {
+        let touch = swapped_panel(x, y);
+        (touch.x as f32, touch.y as f32)
+    });
+
+    let calibration = TouchCalibration::from_samples(&raw, &[(0.0, 0.0), (100.0, 0.0), (0.0, 200.0)]).unwrap();
+
+    assert_eq!(calibration.position(&swapped_panel(123.0, 45.0), Screen::default()), Position::new(20, 4));
+    assert_eq!(calibration.position(&swapped_panel(239.0, 319.0), Screen::default()), Position::new(WIDTH - 1, HEIGHT - 1));
+}
+
+#[test]
+fn rejects_aligned_samples() {
+    let points = [(0.0, 0.0), (10.0, 10.0), (20.0, 20.0)];
+
+    assert!(TouchCalibration::from_samples(&points, &points).is_err());
+}
+
+#[test]
+fn targets_do_not_cover_the_title() {
+    let models = [DisplayModel::Ili9341, DisplayModel::St7789Square, DisplayModel::Ili9488];
+    let orientations = [ScreenOrientation::Portrait, ScreenOrientation::Landscape];
+
+    for model in models {
+        for orientation in orientations {
+            let screen = Screen::new(model, orientation);
+            let (width, height) = screen.size();
+
+            for index in 0..targets(screen).len() {
+                let mut phone = phone();
+                phone.phone_data.screen = screen;
+                let mut app = CalibrationApp::new();
+                app.state = CalibrationAppState::Target(index);
+
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal.draw(|frame| {
+                    app.render(&mut phone.phone_data, frame, Rect::new(0, 2, width, height - 2)).unwrap();
+                }).unwrap();
+
+                let buffer = terminal.backend().buffer();
+                let rows = (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>());
+                let title = rows.filter(|row| row.contains("Tap the crosshair")).collect::<Vec<_>>();
+
+                assert_eq!(title.len(), 1, "{:?} {:?}", model, orientation);
+                assert!(!title[0].contains(['┼', '─', '│']), "{:?} {:?}: {}", model, orientation, title[0]);
+            }
+        }
+    }
+}
+
+#[test]
+fn calibration_app() {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+
+    let mut records = Vec::new();
+    // Launch the app, then start
+    tap(&mut records, 0, default_panel(Position::new(20, 6)));
+    tap(&mut records, 1000, default_panel(Position::new(20, 10)));
+
+    for (index, target) in targets(Screen::default()).into_iter().enumerate() {
+        let (x, y) = target_pixel(target, Screen::default());
+        tap(&mut records, 2000 + index as u64 * 1000, swapped_panel(x, y));
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    phone.replay(&mut terminal, records).unwrap();
+
+    assert!(matches!(phone.state, PhoneState::InApp(1)));
+
+    let calibration = phone.phone_data.touch.lock().unwrap().calibration;
+    assert_eq!(calibration.position(&swapped_panel(123.0, 45.0), Screen::default()), Position::new(20, 4));
+}
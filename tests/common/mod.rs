@@ -0,0 +1,135 @@
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use embedded_svc::wifi::{AccessPointInfo, AuthMethod, Configuration};
+use esp32_phone_os::phone::Phone;
+use esp32_phone_os::platform::{SettingsStore, Storage, TimeSource, WifiController};
+
+/// Cell grid of the default screen, a portrait ILI9341
+pub const WIDTH: u16 = 40;
+pub const HEIGHT: u16 = 32;
+
+/// Always displays 12:34
+pub struct FixedTime;
+
+impl TimeSource for FixedTime {
+    fn synchronize(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn desynchronize(&mut self) {}
+
+    fn is_synchronized(&self) -> bool {
+        true
+    }
+
+    fn now(&self) -> Duration {
+        Duration::from_secs(12 * 3600 + 34 * 60)
+    }
+}
+
+/// WiFi station seeing a fixed list of access points, connecting succeeds instantly
+pub struct MockWifi {
+    pub access_points: Vec<AccessPointInfo>,
+    pub configuration: Configuration,
+    pub connected: bool,
+}
+
+impl MockWifi {
+    pub fn new() -> Self {
+        MockWifi {
+            access_points: vec![
+                access_point("Wokwi-GUEST", AuthMethod::None),
+                access_point("Home", AuthMethod::WPA2Personal),
+            ],
+            configuration: Configuration::None,
+            connected: false,
+        }
+    }
+}
+
+impl WifiController for MockWifi {
+    fn scan(&mut self) -> anyhow::Result<Vec<AccessPointInfo>> {
+        Ok(self.access_points.clone())
+    }
+
+    fn get_configuration(&self) -> anyhow::Result<Configuration> {
+        Ok(self.configuration.clone())
+    }
+
+    fn set_configuration(&mut self, configuration: &Configuration) -> anyhow::Result<()> {
+        self.configuration = configuration.clone();
+        self.connected = false;
+        Ok(())
+    }
+
+    fn connect(&mut self) -> anyhow::Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> anyhow::Result<bool> {
+        Ok(self.connected)
+    }
+}
+
+pub fn access_point(ssid: &str, auth_method: AuthMethod) -> AccessPointInfo {
+    AccessPointInfo {
+        ssid: heapless::String::from_str(ssid).unwrap(),
+        auth_method: Some(auth_method),
+        ..Default::default()
+    }
+}
+
+pub fn phone() -> Phone {
+    Phone::new(Box::new(FixedTime))
+}
+
+/// Empty directory of its own, standing in for the SD card, removed once every clone is dropped
+#[derive(Clone)]
+pub struct TempStorage(pub PathBuf, Arc<TempDirectory>);
+
+struct TempDirectory(PathBuf);
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl TempStorage {
+    pub fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let root = std::env::temp_dir().join(format!("phone-os-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        TempStorage(root.clone(), Arc::new(TempDirectory(root)))
+    }
+}
+
+impl Storage for TempStorage {
+    fn root(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// NVS partition in memory, shared by the clones to outlive a reboot
+#[derive(Clone, Default)]
+pub struct MemorySettings(pub Arc<Mutex<Option<String>>>);
+
+impl SettingsStore for MemorySettings {
+    fn load(&self) -> anyhow::Result<Option<String>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn save(&mut self, content: &str) -> anyhow::Result<()> {
+        *self.0.lock().unwrap() = Some(String::from(content));
+        Ok(())
+    }
+}
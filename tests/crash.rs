@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use anyhow::anyhow;
+use crossbeam_channel::unbounded;
+use esp32_phone_os::crash::{self, CrashRecord, CRASH_LOG_PATH, LAST_CRASH_PATH};
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::platform::Storage;
+use mousefood::prelude::Terminal;
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, TempStorage, HEIGHT, WIDTH};
+
+mod common;
+
+/// The panic hook is global, so everything goes through a single test
+#[test]
+fn crashes_are_saved_and_reported_on_the_next_boot() {
+    let storage = TempStorage::new();
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(storage.clone()));
+    phone.init().unwrap();
+    crash::install_panic_hook(&phone);
+
+    assert_eq!(crash::catch(|| Ok(())), None);
+
+    let record = crash::catch(|| panic!("No WiFi")).unwrap();
+
+    assert_eq!(record.message, "No WiFi");
+    assert!(record.location.as_ref().unwrap().starts_with("tests/crash.rs"));
+    assert!(!crash::panicked());
+
+    // Errors end on the crash screen as well
+    let record = crash::catch(|| Err(anyhow!("Display unplugged"))).unwrap();
+    assert_eq!(record.message, "Display unplugged");
+    assert_eq!(record.location, None);
+
+    let log = fs::read_to_string(storage.0.join(CRASH_LOG_PATH)).unwrap();
+    let records: Vec<CrashRecord> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].message, "No WiFi");
+
+    // Nothing is written once the card is gone
+    *phone.crash_storage_root.lock().unwrap() = None;
+    crash::catch(|| Err(anyhow!("Card removed"))).unwrap();
+    assert_eq!(fs::read_to_string(storage.0.join(CRASH_LOG_PATH)).unwrap(), log);
+
+    // Only the last one is shown on the next boot, once
+    let mut rebooted = common::phone();
+    rebooted.phone_data.storage = Some(Box::new(storage.clone()));
+    rebooted.init().unwrap();
+
+    assert_eq!(rebooted.phone_data.dialogs.len(), 1);
+    assert!(rebooted.phone_data.dialogs[0].message.contains("Display unplugged"));
+    assert!(!storage.root().join(LAST_CRASH_PATH).exists());
+
+    let mut rebooted = common::phone();
+    rebooted.phone_data.storage = Some(Box::new(storage));
+    rebooted.init().unwrap();
+    assert!(rebooted.phone_data.dialogs.is_empty());
+}
+
+#[test]
+fn a_corrupted_crash_record_does_not_prevent_booting() {
+    let storage = TempStorage::new();
+    storage.write(Path::new(LAST_CRASH_PATH), b"{").unwrap();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(storage));
+    phone.init().unwrap();
+
+    assert!(phone.phone_data.dialogs.is_empty());
+}
+
+#[test]
+fn the_crash_screen_reboots_without_a_tap() {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    // Kept open, as by a background thread parked by the panic hook
+    let (_sender, events) = unbounded::<PhoneEvent>();
+    let record = CrashRecord::new("Touch thread panicked", None);
+
+    let start = Instant::now();
+    crash::wait_for_reboot(&mut terminal, &events, &record, Duration::from_millis(300)).unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,242 @@
+use std::time::Instant;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use esp32_phone_os::apps::app::{App, AppHandler, AppImpl};
+use esp32_phone_os::events::{AppEvent, CoreEvent, EventType};
+use esp32_phone_os::events::gestures::Gesture;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::phone::{Phone, PhoneData};
+use esp32_phone_os::state::PhoneState;
+use esp32_phone_os::ui::widgets::dialog::Dialog;
+use esp32_phone_os::ui::widgets::toast::{Toast, ToastLevel};
+use mousefood::prelude::{Frame, Position, Rect, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, HEIGHT, WIDTH};
+
+mod common;
+
+#[derive(Debug)]
+enum FaultyEvent {
+    Ask,
+    Confirmed,
+    Fail,
+    Break,
+}
+
+/// Fails on demand, answers its confirmations with a toast
+struct Faulty {
+    broken: bool,
+}
+
+#[async_trait(?Send)]
+impl AppHandler for Faulty {
+    type Event = FaultyEvent;
+
+    fn new() -> Self where Self: Sized {
+        Faulty { broken: false }
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Faulty"
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        match self.broken {
+            true => Err(anyhow!("Nothing to draw")),
+            false => Ok(EventType::default()),
+        }
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &FaultyEvent) -> anyhow::Result<Option<PhoneState>> {
+        match event {
+            FaultyEvent::Ask => phone_data.show_dialog(Dialog::confirm("Delete", "Delete everything?", FaultyEvent::Confirmed)),
+            FaultyEvent::Confirmed => phone_data.show_toast(Toast::info("Deleted")),
+            FaultyEvent::Fail => return Err(anyhow!("Disk full")),
+            FaultyEvent::Break => self.broken = true,
+        }
+
+        Ok(None)
+    }
+}
+
+/// Handles the events of `Faulty` too, without asking anything
+struct Mirror;
+
+#[async_trait(?Send)]
+impl AppHandler for Mirror {
+    type Event = FaultyEvent;
+
+    fn new() -> Self where Self: Sized {
+        Mirror
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Mirror"
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        Ok(EventType::default())
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &FaultyEvent) -> anyhow::Result<Option<PhoneState>> {
+        if let FaultyEvent::Confirmed = event {
+            phone_data.show_toast(Toast::info("Mirrored"));
+        }
+
+        Ok(None)
+    }
+}
+
+struct Device {
+    phone: Phone,
+    terminal: Terminal<TestBackend>,
+    current_events: Option<EventType>,
+}
+
+impl Device {
+    fn new() -> Self {
+        let mut phone = phone();
+        phone.apps = vec![AppImpl::<Faulty>::new_boxed()];
+        phone.set_state(PhoneState::InApp(0)).unwrap();
+
+        let mut device = Device {
+            phone,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            current_events: None,
+        };
+        device.phone.redraw(&mut device.terminal, &mut device.current_events).unwrap();
+
+        device
+    }
+
+    fn step(&mut self, event: Option<PhoneEvent>) {
+        self.phone.step(&mut self.terminal, &mut self.current_events, event).unwrap();
+    }
+
+    fn post(&mut self, event: impl AppEvent) {
+        self.step(Some(PhoneEvent::App(Box::new(event))));
+    }
+
+    fn tap_text(&mut self, text: &str) {
+        let buffer = self.terminal.backend().buffer();
+        let position = (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| Position::new(x, y)))
+            .find(|position| {
+                let line: String = (position.x..WIDTH).map(|x| buffer[(x, position.y)].symbol()).collect();
+                line.starts_with(text)
+            })
+            .unwrap_or_else(|| panic!("{} is not on screen", text));
+
+        self.step(Some(PhoneEvent::Gesture(Gesture::Tap(position))));
+    }
+
+    fn screen_text(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+}
+
+#[test]
+fn confirming_posts_the_event_back() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Ask);
+    assert!(device.screen_text().contains("Delete everything?"));
+
+    device.tap_text("OK");
+    assert!(device.phone.phone_data.dialogs.is_empty());
+    assert_eq!(device.phone.phone_data.toast.as_ref().unwrap().text, "Deleted");
+}
+
+#[test]
+fn only_the_app_which_asked_gets_the_answer() {
+    let mut device = Device::new();
+    device.phone.apps.push(AppImpl::<Mirror>::new_boxed());
+
+    device.post(FaultyEvent::Ask);
+    device.tap_text("OK");
+    assert_eq!(device.phone.phone_data.toast.as_ref().unwrap().text, "Deleted");
+}
+
+#[test]
+fn cancel_and_back_dismiss_the_dialog() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Ask);
+    device.tap_text("Cancel");
+    assert!(device.phone.phone_data.dialogs.is_empty());
+    assert!(device.phone.phone_data.toast.is_none());
+
+    // Back closes the dialog, not the app
+    device.post(FaultyEvent::Ask);
+    device.post(CoreEvent::Back);
+    assert!(device.phone.phone_data.dialogs.is_empty());
+    assert!(device.phone.phone_data.toast.is_none());
+    assert_eq!(device.phone.state, PhoneState::InApp(0));
+}
+
+#[test]
+fn the_same_dialog_is_queued_once() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Ask);
+    device.post(FaultyEvent::Ask);
+    device.phone.phone_data.show_dialog(Dialog::alert("Update", "Restart to update"));
+    assert_eq!(device.phone.phone_data.dialogs.len(), 2);
+
+    // Shown one after the other
+    device.tap_text("Cancel");
+    assert!(device.screen_text().contains("Restart to update"));
+}
+
+#[test]
+fn a_failing_event_becomes_a_toast() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Fail);
+    let toast = device.phone.phone_data.toast.as_ref().unwrap();
+    assert_eq!(toast.level, ToastLevel::Error);
+    assert!(device.screen_text().contains("Disk full"));
+    assert_eq!(device.phone.state, PhoneState::InApp(0));
+
+    device.tap_text("Disk full");
+    assert!(device.phone.phone_data.toast.is_none());
+    assert!(!device.screen_text().contains("Disk full"));
+}
+
+#[test]
+fn toasts_go_away_on_their_own() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Fail);
+    device.phone.phone_data.toast.as_mut().unwrap().expires = Instant::now();
+
+    device.step(None);
+    assert!(device.phone.phone_data.toast.is_none());
+    assert!(!device.screen_text().contains("Disk full"));
+}
+
+#[test]
+fn a_failing_render_offers_to_leave_the_app() {
+    let mut device = Device::new();
+
+    device.post(FaultyEvent::Break);
+    assert!(device.screen_text().contains("Faulty failed"));
+    assert!(device.screen_text().contains("Nothing to draw"));
+
+    // Redrawing the broken app does not stack up dialogs
+    device.step(None);
+    assert_eq!(device.phone.phone_data.dialogs.len(), 1);
+
+    device.tap_text("OK");
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+    assert!(device.phone.phone_data.dialogs.is_empty());
+}
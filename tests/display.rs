@@ -0,0 +1,45 @@
+use esp32_phone_os::drivers::display::{DisplayModel, Screen, ScreenOrientation};
+use esp32_phone_os::drivers::touch::TouchPoint;
+use esp32_phone_os::events::calibration::TouchCalibration;
+use mousefood::prelude::Position;
+
+#[test]
+fn grid_follows_resolution() {
+    let grids = [
+        (DisplayModel::Ili9341, ScreenOrientation::Portrait, (40, 32)),
+        (DisplayModel::Ili9341, ScreenOrientation::Landscape, (53, 24)),
+        (DisplayModel::St7789, ScreenOrientation::Portrait, (40, 32)),
+        (DisplayModel::St7789Square, ScreenOrientation::Portrait, (40, 24)),
+        (DisplayModel::St7789Square, ScreenOrientation::Landscape, (40, 24)),
+        (DisplayModel::Ili9488, ScreenOrientation::Portrait, (53, 48)),
+        (DisplayModel::Ili9488, ScreenOrientation::Landscape, (80, 32)),
+    ];
+
+    for (model, orientation, grid) in grids {
+        assert_eq!(Screen::new(model, orientation).size(), grid, "{:?} {:?}", model, orientation);
+    }
+}
+
+#[test]
+fn rotation_round_trip() {
+    let screen = Screen::new(DisplayModel::Ili9488, ScreenOrientation::Landscape);
+
+    assert_eq!(screen.rotate(0.0, 0.0), (0.0, 319.0));
+    assert_eq!(screen.unrotate(0.0, 319.0), (0.0, 0.0));
+    assert_eq!(screen.unrotate(479.0, 0.0), (319.0, 479.0));
+}
+
+#[test]
+fn default_calibration_fits_panel() {
+    let screen = Screen::new(DisplayModel::Ili9488, ScreenOrientation::Portrait);
+    let calibration = TouchCalibration::for_model(DisplayModel::Ili9488);
+
+    let corner = TouchPoint {
+        x: 0,
+        y: 0,
+        id: 0,
+        event: None,
+    };
+
+    assert_eq!(calibration.position(&corner, screen), Position::new(52, 47));
+}
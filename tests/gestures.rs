@@ -0,0 +1,156 @@
+use std::time::{Duration, Instant};
+use esp32_phone_os::drivers::display::{DisplayModel, Screen, ScreenOrientation};
+use esp32_phone_os::drivers::touch::{TouchEvent, TouchPoint};
+use esp32_phone_os::events::calibration::TouchCalibration;
+use esp32_phone_os::events::gestures::{Gesture, GestureRecognizer, SwipeDirection, Zoom};
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::ui::widgets::keyboard::{KeyboardLayout, KEYBOARD_HEIGHT};
+use mousefood::prelude::{Position, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, HEIGHT, WIDTH};
+
+mod common;
+
+/// Raw FT6206 point mapped to a cell
+fn touch(x: u16, y: u16, event: TouchEvent) -> TouchPoint {
+    TouchPoint {
+        x: 240 - x * 6,
+        y: 319 - (y * 10 + 5),
+        id: 0,
+        event: Some(event),
+    }
+}
+
+/// Feeds `(milliseconds, touches)` reads and collects every gesture
+fn recognize(reads: &[(u64, Vec<TouchPoint>)]) -> Vec<Gesture> {
+    let start = Instant::now();
+    let mut recognizer = GestureRecognizer::new();
+
+    reads
+        .iter()
+        .flat_map(|(ms, touches)| recognizer.update(touches, start + Duration::from_millis(*ms)))
+        .collect()
+}
+
+#[test]
+fn default_calibration_maps_cells() {
+    let calibration = TouchCalibration::default();
+
+    assert_eq!(calibration.position(&touch(0, 0, TouchEvent::Press), Screen::default()), Position::new(0, 0));
+    assert_eq!(calibration.position(&touch(20, 3, TouchEvent::Press), Screen::default()), Position::new(20, 3));
+    assert_eq!(calibration.position(&touch(39, 31, TouchEvent::Press), Screen::default()), Position::new(39, 31));
+}
+
+#[test]
+fn landscape_rotates_touches() {
+    let calibration = TouchCalibration::default();
+    let landscape = Screen::new(DisplayModel::Ili9341, ScreenOrientation::Landscape);
+
+    // Top left of the portrait panel ends up bottom left in landscape
+    assert_eq!(calibration.position(&touch(0, 0, TouchEvent::Press), landscape), Position::new(0, 23));
+    assert_eq!(calibration.position(&touch(39, 31, TouchEvent::Press), landscape), Position::new(52, 0));
+}
+
+#[test]
+fn tap() {
+    let gestures = recognize(&[
+        (0, vec![touch(5, 5, TouchEvent::Press)]),
+        (20, vec![touch(5, 5, TouchEvent::Move)]),
+        (40, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![Gesture::Tap(Position::new(5, 5))]);
+}
+
+#[test]
+fn double_tap() {
+    let gestures = recognize(&[
+        (0, vec![touch(5, 5, TouchEvent::Press)]),
+        (40, vec![]),
+        (200, vec![touch(5, 5, TouchEvent::Press)]),
+        (240, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![
+        Gesture::Tap(Position::new(5, 5)),
+        Gesture::Tap(Position::new(5, 5)),
+        Gesture::DoubleTap(Position::new(5, 5)),
+    ]);
+}
+
+/// Typing the same letter twice quickly is a double tap as well
+#[test]
+fn double_tapping_a_key_types_it_twice() {
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    phone.phone_data.display_keyboard(KeyboardLayout::Azerty, false);
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let key = (HEIGHT - KEYBOARD_HEIGHT..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| Position::new(x, y)))
+        .find(|position| buffer[(position.x, position.y)].symbol() == "a")
+        .unwrap();
+
+    for gesture in [Gesture::Tap(key), Gesture::Tap(key), Gesture::DoubleTap(key)] {
+        phone.step(&mut terminal, &mut current_events, Some(PhoneEvent::Gesture(gesture))).unwrap();
+    }
+
+    assert_eq!(phone.phone_data.keyboard.as_ref().unwrap().text, "aa");
+}
+
+#[test]
+fn long_press() {
+    let gestures = recognize(&[
+        (0, vec![touch(5, 5, TouchEvent::Press)]),
+        (300, vec![touch(5, 5, TouchEvent::Move)]),
+        (700, vec![touch(5, 5, TouchEvent::Move)]),
+        (900, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![Gesture::LongPress(Position::new(5, 5))]);
+}
+
+#[test]
+fn drag_and_swipe() {
+    let gestures = recognize(&[
+        (0, vec![touch(5, 20, TouchEvent::Press)]),
+        (20, vec![touch(5, 17, TouchEvent::Move)]),
+        (40, vec![touch(5, 12, TouchEvent::Move)]),
+        (60, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![
+        Gesture::Drag { from: Position::new(5, 20), to: Position::new(5, 17) },
+        Gesture::Drag { from: Position::new(5, 17), to: Position::new(5, 12) },
+        Gesture::Swipe { from: Position::new(5, 20), direction: SwipeDirection::Up },
+    ]);
+}
+
+#[test]
+fn slow_drag_is_not_a_swipe() {
+    let gestures = recognize(&[
+        (0, vec![touch(5, 20, TouchEvent::Press)]),
+        (500, vec![touch(15, 20, TouchEvent::Move)]),
+        (1000, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![Gesture::Drag { from: Position::new(5, 20), to: Position::new(15, 20) }]);
+}
+
+#[test]
+fn pinch() {
+    let gestures = recognize(&[
+        (0, vec![touch(18, 15, TouchEvent::Press), touch(22, 15, TouchEvent::Press)]),
+        (20, vec![touch(10, 15, TouchEvent::Move), touch(30, 15, TouchEvent::Move)]),
+        (40, vec![touch(19, 15, TouchEvent::Move), touch(21, 15, TouchEvent::Move)]),
+        (60, vec![]),
+    ]);
+
+    assert_eq!(gestures, vec![
+        Gesture::Pinch { center: Position::new(20, 15), zoom: Zoom::In },
+        Gesture::Pinch { center: Position::new(20, 15), zoom: Zoom::Out },
+    ]);
+}
@@ -0,0 +1,229 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use esp32_phone_os::apps::app::{AppHandler, AppImpl, App};
+use esp32_phone_os::apps::calibration::CalibrationEvent;
+use esp32_phone_os::apps::wifi::WifiEvent;
+use esp32_phone_os::events::EventType;
+use esp32_phone_os::navigation::DeepLink;
+use esp32_phone_os::phone::{Phone, PhoneData};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Frame, Rect, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MockWifi, HEIGHT, WIDTH};
+
+mod common;
+
+/// Hooks called on the recorder apps, in order
+static CALLS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+struct Recorder;
+
+#[async_trait(?Send)]
+impl AppHandler for Recorder {
+    type Event = ();
+
+    fn new() -> Self where Self: Sized {
+        Recorder
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Recorder"
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        Ok(EventType::default())
+    }
+
+    async fn handle_event(&mut self, _phone_data: &mut PhoneData, _event: &()) -> anyhow::Result<Option<PhoneState>> {
+        Ok(None)
+    }
+
+    async fn on_enter(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        record("enter")
+    }
+
+    async fn on_exit(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        record("exit")
+    }
+
+    async fn on_pause(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        record("pause")
+    }
+
+    async fn suspend(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        record("suspend")
+    }
+}
+
+/// Suspends counted by `Suspended`
+static SUSPENDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails to suspend when `FAILING`, counts its suspends otherwise
+struct Suspended<const FAILING: bool>;
+
+#[async_trait(?Send)]
+impl<const FAILING: bool> AppHandler for Suspended<FAILING> {
+    type Event = ();
+
+    fn new() -> Self where Self: Sized {
+        Suspended
+    }
+
+    fn app_name(&self) -> &'static str {
+        match FAILING {
+            true => "Failing",
+            false => "Suspended",
+        }
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        Ok(EventType::default())
+    }
+
+    async fn handle_event(&mut self, _phone_data: &mut PhoneData, _event: &()) -> anyhow::Result<Option<PhoneState>> {
+        Ok(None)
+    }
+
+    async fn suspend(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        match FAILING {
+            true => Err(anyhow!("Out of luck")),
+            false => {
+                SUSPENDS.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn record(hook: &str) -> anyhow::Result<()> {
+    CALLS.lock().unwrap().push(hook.to_string());
+    Ok(())
+}
+
+fn calls() -> Vec<String> {
+    CALLS.lock().unwrap().drain(..).collect()
+}
+
+#[test]
+fn hooks_follow_the_foreground() {
+    let mut phone = phone();
+    phone.apps = vec![AppImpl::<Recorder>::new_boxed(), AppImpl::<Recorder>::new_boxed()];
+
+    phone.set_state(PhoneState::InApp(0)).unwrap();
+    assert_eq!(calls(), ["enter"]);
+
+    // The first app is covered, not left
+    phone.set_state(PhoneState::InApp(1)).unwrap();
+    assert_eq!(calls(), ["pause", "enter"]);
+    assert_eq!(phone.navigation.len(), 2);
+
+    // Memory pressure only reaches the background
+    phone.suspend_background_apps().unwrap();
+    assert_eq!(calls(), ["suspend"]);
+
+    phone.set_state(PhoneState::InApp(1)).unwrap();
+    assert!(calls().is_empty());
+
+    // Going home leaves both
+    phone.set_state(PhoneState::Homepage).unwrap();
+    assert_eq!(calls(), ["exit", "exit"]);
+    assert!(phone.navigation.is_empty());
+}
+
+#[test]
+fn a_failing_suspend_does_not_stop_the_others() {
+    let mut phone = phone();
+    phone.apps = vec![AppImpl::<Suspended<true>>::new_boxed(), AppImpl::<Suspended<false>>::new_boxed()];
+
+    let error = phone.suspend_background_apps().unwrap_err();
+    assert_eq!(error.to_string(), "Could not suspend Failing");
+    assert_eq!(SUSPENDS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn calibration_starts_over_after_leaving() {
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    phone.set_state(PhoneState::InApp(1)).unwrap();
+    phone.dispatch_event(&CalibrationEvent::Start).unwrap();
+    phone.set_state(PhoneState::Homepage).unwrap();
+    phone.set_state(PhoneState::InApp(1)).unwrap();
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+    assert!(screen.contains("Start"));
+}
+
+#[test]
+fn the_password_screen_survives_a_pause() {
+    let mut phone = typing_password();
+    phone.phone_data.keyboard.as_mut().unwrap().text = String::from("hunter2");
+
+    // The calibration app covers the WiFi settings, without their keyboard
+    phone.set_state(PhoneState::InApp(1)).unwrap();
+    assert!(phone.phone_data.keyboard.is_none());
+
+    phone.back().unwrap();
+    assert_eq!(phone.state, PhoneState::InApp(0));
+    assert_eq!(phone.phone_data.keyboard.as_ref().unwrap().text, "hunter2");
+    assert!(screen_text(&mut phone).contains("Connect"));
+}
+
+#[test]
+fn a_rescan_keeps_the_password_screen() {
+    let mut phone = typing_password();
+
+    phone.dispatch_event(&WifiEvent::Scan).unwrap();
+    assert!(screen_text(&mut phone).contains("Connect"));
+
+    settle(&mut phone);
+    assert!(screen_text(&mut phone).contains("Connect"));
+    assert!(phone.phone_data.keyboard.is_some());
+}
+
+/// On the password screen of the Home access point
+fn typing_password() -> Phone {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+    settle(&mut phone);
+
+    phone.open(&DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(String::from("Home")))).unwrap();
+    assert!(phone.phone_data.keyboard.is_some());
+
+    phone
+}
+
+/// Handles the events posted by the background tasks, e.g. scans
+fn settle(phone: &mut Phone) {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.phone_data.runtime.wait_idle();
+
+    while let Ok(event) = phone.events.try_recv() {
+        phone.step(&mut terminal, &mut current_events, Some(event)).unwrap();
+        phone.phone_data.runtime.wait_idle();
+    }
+}
+
+fn screen_text(phone: &mut Phone) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    buffer.content().iter().map(|cell| cell.symbol()).collect()
+}
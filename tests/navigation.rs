@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+use embedded_svc::wifi::Configuration;
+use esp32_phone_os::apps::wifi::WifiEvent;
+use esp32_phone_os::events::{AppEvent, CoreEvent, EventType};
+use esp32_phone_os::events::gestures::{Gesture, SwipeDirection};
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::navigation::DeepLink;
+use esp32_phone_os::phone::Phone;
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Position, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MockWifi, HEIGHT, WIDTH};
+
+mod common;
+
+struct Device {
+    phone: Phone,
+    terminal: Terminal<TestBackend>,
+    current_events: Option<EventType>,
+}
+
+impl Device {
+    fn new() -> Self {
+        let mut phone = phone();
+        phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+        phone.init().unwrap();
+
+        let mut device = Device {
+            phone,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+            current_events: None,
+        };
+        device.settle();
+
+        device
+    }
+
+    fn step(&mut self, event: PhoneEvent) {
+        self.phone.step(&mut self.terminal, &mut self.current_events, Some(event)).unwrap();
+        self.settle();
+    }
+
+    /// Handles the events posted by the background tasks, e.g. scans
+    fn settle(&mut self) {
+        self.phone.phone_data.runtime.wait_idle();
+
+        while let Ok(event) = self.phone.events.try_recv() {
+            self.phone.step(&mut self.terminal, &mut self.current_events, Some(event)).unwrap();
+            self.phone.phone_data.runtime.wait_idle();
+        }
+
+        self.phone.redraw(&mut self.terminal, &mut self.current_events).unwrap();
+    }
+
+    fn post(&mut self, event: impl AppEvent) {
+        self.step(PhoneEvent::App(Box::new(event)));
+    }
+
+    fn tap_text(&mut self, text: &str) {
+        let position = self.position_of(text);
+        self.step(PhoneEvent::Gesture(Gesture::Tap(position)));
+    }
+
+    fn position_of(&self, text: &str) -> Position {
+        let buffer = self.terminal.backend().buffer();
+
+        (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| Position::new(x, y)))
+            .find(|position| {
+                let line: String = (position.x..WIDTH).map(|x| buffer[(x, position.y)].symbol()).collect();
+                line.starts_with(text)
+            })
+            .unwrap_or_else(|| panic!("{} is not on screen", text))
+    }
+
+    fn screen_text(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+}
+
+#[test]
+fn back_walks_the_screen_history() {
+    let mut device = Device::new();
+
+    device.tap_text("WiFi settings");
+    device.tap_text("Home");
+    assert!(device.phone.phone_data.keyboard.is_some());
+
+    device.tap_text("← Go back");
+    assert!(device.phone.phone_data.keyboard.is_none());
+    assert!(device.screen_text().contains("Access points"));
+
+    // The app name in the state bar goes back as well
+    device.tap_text("← WiFi settings");
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+    assert!(device.phone.navigation.is_empty());
+}
+
+#[test]
+fn swipe_from_the_left_edge_goes_back() {
+    let mut device = Device::new();
+    device.tap_text("Display settings");
+
+    // Elsewhere, swipes are left to the apps
+    device.step(PhoneEvent::Gesture(Gesture::Swipe { from: Position::new(10, 10), direction: SwipeDirection::Right }));
+    assert_eq!(device.phone.state, PhoneState::InApp(2));
+
+    device.step(PhoneEvent::Gesture(Gesture::Swipe { from: Position::new(0, 10), direction: SwipeDirection::Right }));
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+}
+
+#[test]
+fn deep_link_opens_the_password_screen() {
+    let mut device = Device::new();
+    device.tap_text("Display settings");
+
+    // Waits for the scan the WiFi app starts on entering
+    device.post(CoreEvent::Open(DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(String::from("Home")))));
+    assert_eq!(device.phone.state, PhoneState::InApp(0));
+    assert!(device.phone.phone_data.keyboard.is_some());
+    assert!(device.screen_text().contains("Connect"));
+
+    // Back to the network list, then to the display settings which opened the link
+    device.tap_text("← Go back");
+    assert!(device.screen_text().contains("Access points"));
+
+    device.tap_text("← Go back");
+    assert_eq!(device.phone.state, PhoneState::InApp(2));
+
+    device.tap_text("← Go back");
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+}
+
+#[test]
+fn a_scan_while_typing_a_password_keeps_the_access_point() {
+    let mut device = Device::new();
+    device.post(CoreEvent::Open(DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(String::from("Home")))));
+
+    // Home went out of reach in the meantime
+    device.post(WifiEvent::Scanned(vec![]));
+    assert!(device.screen_text().contains("Home"));
+
+    device.tap_text("Connect");
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+
+    let configuration = device.phone.phone_data.wifi.as_ref().unwrap().lock().unwrap().get_configuration().unwrap();
+    assert!(matches!(configuration, Configuration::Client(client) if client.ssid == "Home"));
+}
+
+#[test]
+fn connecting_does_not_wait_for_a_running_scan() {
+    let mut device = Device::new();
+    device.post(CoreEvent::Open(DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(String::from("Home")))));
+
+    // As a scan on a worker thread would
+    let wifi = device.phone.phone_data.wifi.clone().unwrap();
+    let scan = wifi.lock().unwrap();
+
+    let connect = device.position_of("Connect");
+    device.phone.step(&mut device.terminal, &mut device.current_events, Some(PhoneEvent::Gesture(Gesture::Tap(connect)))).unwrap();
+    assert_eq!(device.phone.state, PhoneState::Homepage);
+
+    drop(scan);
+    device.settle();
+
+    let configuration = wifi.lock().unwrap().get_configuration().unwrap();
+    assert!(matches!(configuration, Configuration::Client(client) if client.ssid == "Home"));
+}
+
+#[test]
+fn a_deep_link_left_before_the_scan_lands_is_dropped() {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+
+    // Waits for the scan started by `init`, then the user goes back
+    phone.open(&DeepLink::new("WiFi settings", WifiEvent::TypePasswordFor(String::from("Home")))).unwrap();
+    phone.set_state(PhoneState::Homepage).unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.phone_data.runtime.wait_idle();
+
+    while let Ok(event) = phone.events.try_recv() {
+        phone.step(&mut terminal, &mut current_events, Some(event)).unwrap();
+    }
+
+    assert_eq!(phone.state, PhoneState::Homepage);
+    assert!(phone.phone_data.keyboard.is_none());
+}
+
+#[test]
+fn unknown_deep_link_fails() {
+    let mut phone = phone();
+
+    assert!(phone.open(&DeepLink::new("Nope", ())).is_err());
+    assert_eq!(phone.state, PhoneState::Homepage);
+}
@@ -0,0 +1,132 @@
+use std::thread;
+use esp32_phone_os::events::CoreEvent;
+use esp32_phone_os::events::gestures::{Gesture, SwipeDirection};
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::navigation::DeepLink;
+use esp32_phone_os::notifications::{Notification, NotificationEvent, Priority, MAX_NOTIFICATIONS};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Position, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, TempStorage, HEIGHT, WIDTH};
+
+mod common;
+
+fn notification(priority: Priority) -> Notification {
+    Notification::new("Test", "Title", "Body", priority)
+}
+
+#[test]
+fn low_priority_does_not_light_the_indicator() {
+    let mut phone = phone();
+
+    phone.phone_data.notify(notification(Priority::Low));
+    assert_eq!(phone.phone_data.notifications.unread(), None);
+
+    phone.phone_data.notify(notification(Priority::Normal));
+    phone.phone_data.notify(notification(Priority::High));
+    assert_eq!(phone.phone_data.notifications.unread(), Some((2, Priority::High)));
+
+    // Seen once the shade is opened
+    phone.handle_notification_event(&NotificationEvent::OpenShade).unwrap();
+    assert_eq!(phone.phone_data.notifications.unread(), None);
+    assert_eq!(phone.phone_data.notifications.len(), 3);
+}
+
+#[test]
+fn notifications_survive_a_reboot() {
+    let storage = TempStorage::new();
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(storage.clone()));
+    phone.init().unwrap();
+    let first = phone.phone_data.notify(notification(Priority::Normal));
+    phone.phone_data.notify(notification(Priority::High));
+    phone.handle_notification_event(&NotificationEvent::Dismiss(first)).unwrap();
+
+    let mut rebooted = common::phone();
+    rebooted.phone_data.storage = Some(Box::new(storage));
+    rebooted.init().unwrap();
+
+    let notifications = rebooted.phone_data.notifications.sorted();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].priority, Priority::High);
+    assert!(!notifications[0].read);
+
+    // Ids keep increasing, so that old dismiss events do not reach new notifications
+    assert!(rebooted.phone_data.notify(notification(Priority::Low)) > first + 1);
+}
+
+#[test]
+fn oldest_notifications_are_dropped() {
+    let mut phone = phone();
+
+    for _ in 0..MAX_NOTIFICATIONS + 3 {
+        phone.phone_data.notify(notification(Priority::Normal));
+    }
+
+    let notifications = phone.phone_data.notifications.sorted();
+    assert_eq!(notifications.len(), MAX_NOTIFICATIONS);
+    assert_eq!(notifications.last().unwrap().id, 3);
+}
+
+#[test]
+fn background_threads_notify_through_the_queue() {
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    let events = phone.phone_data.events.clone();
+    thread::spawn(move || events.notify(notification(Priority::Normal))).join().unwrap();
+
+    let event = phone.events.try_recv().unwrap();
+    phone.step(&mut terminal, &mut current_events, Some(event)).unwrap();
+    assert_eq!(phone.phone_data.notifications.unread(), Some((1, Priority::Normal)));
+
+    // Pulled down from the state bar
+    let swipe = Gesture::Swipe { from: Position::new(20, 0), direction: SwipeDirection::Down };
+    phone.step(&mut terminal, &mut current_events, Some(PhoneEvent::Gesture(swipe))).unwrap();
+    assert!(phone.shade_open);
+
+    // Back closes the shade before leaving anything
+    phone.dispatch_event(&CoreEvent::Back).unwrap();
+    assert!(!phone.shade_open);
+}
+
+#[test]
+fn tapping_a_notification_runs_its_action() {
+    let mut phone = phone();
+    let id = phone.phone_data.notify(
+        notification(Priority::High).with_action(CoreEvent::Open(DeepLink::new("Display settings", ())))
+    );
+
+    phone.handle_notification_event(&NotificationEvent::OpenShade).unwrap();
+    let state = phone.handle_notification_event(&NotificationEvent::Act(id)).unwrap();
+
+    assert!(state.is_none());
+    assert_eq!(phone.state, PhoneState::InApp(2));
+    assert!(!phone.shade_open);
+    assert!(phone.phone_data.notifications.is_empty());
+}
+
+#[test]
+fn dragging_scrolls_the_shade() {
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    for _ in 0..20 {
+        phone.phone_data.notify(notification(Priority::Normal));
+    }
+
+    phone.handle_notification_event(&NotificationEvent::OpenShade).unwrap();
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    // The finger goes up by two notifications
+    let drag = Gesture::Drag { from: Position::new(20, 20), to: Position::new(20, 14) };
+    phone.step(&mut terminal, &mut current_events, Some(PhoneEvent::Gesture(drag))).unwrap();
+    assert_eq!(phone.shade_list.offset(), 2);
+
+    // Opened again from the top
+    phone.handle_notification_event(&NotificationEvent::OpenShade).unwrap();
+    assert_eq!(phone.shade_list.offset(), 0);
+}
@@ -0,0 +1,109 @@
+use std::convert::Infallible;
+use std::time::Duration;
+use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+use embedded_graphics::prelude::{DrawTarget, Dimensions, OriginDimensions, Pixel, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use esp32_phone_os::drivers::framebuffer::DirtyFramebuffer;
+use esp32_phone_os::ui::redraw::RedrawScheduler;
+use mousefood::prelude::Terminal;
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, HEIGHT, WIDTH};
+
+mod common;
+
+/// Remembers the areas pushed to it
+struct SpiDisplay {
+    pushed: Vec<Rectangle>,
+}
+
+impl OriginDimensions for SpiDisplay {
+    fn size(&self) -> Size {
+        Size::new(240, 320)
+    }
+}
+
+impl DrawTarget for SpiDisplay {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        assert_eq!(colors.into_iter().count(), area.size.width as usize * area.size.height as usize);
+        self.pushed.push(*area);
+        Ok(())
+    }
+}
+
+#[test]
+fn clock_redraws_once_a_minute() {
+    let mut redraw = RedrawScheduler::default();
+    assert!(redraw.should_redraw(Duration::from_secs(0)));
+
+    redraw.drawn(Duration::from_secs(0));
+    assert!(!redraw.should_redraw(Duration::from_secs(59)));
+    assert!(redraw.should_redraw(Duration::from_secs(60)));
+
+    redraw.drawn(Duration::from_secs(60));
+    redraw.mark_dirty();
+    assert!(redraw.should_redraw(Duration::from_secs(61)));
+}
+
+#[test]
+fn idle_phone_does_not_redraw() {
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+    phone.step(&mut terminal, &mut current_events, None).unwrap();
+
+    assert!(!phone.phone_data.redraw.is_dirty());
+}
+
+#[test]
+fn only_changed_cells_are_pushed() {
+    let mut display = SpiDisplay { pushed: vec![] };
+    let mut framebuffer = DirtyFramebuffer::new(&mut display);
+
+    // The first flush pushes everything, a row of cells at a time
+    assert_eq!(framebuffer.flush().unwrap(), 40 * 32);
+
+    // Two pixels in neighbouring cells, and one that does not change
+    framebuffer.draw_iter([
+        Pixel(Point::new(7, 12), Rgb565::WHITE),
+        Pixel(Point::new(12, 15), Rgb565::WHITE),
+        Pixel(Point::new(100, 100), Rgb565::BLACK),
+    ]).unwrap();
+    assert_eq!(framebuffer.flush().unwrap(), 2);
+    assert_eq!(framebuffer.flush().unwrap(), 0);
+
+    assert_eq!(display.pushed.len(), 32 + 1);
+    assert_eq!(display.pushed[32], Rectangle::new(Point::new(6, 10), Size::new(12, 10)));
+}
+
+#[test]
+fn redrawing_the_whole_screen_pushes_only_changed_cells() {
+    let mut display = SpiDisplay { pushed: vec![] };
+    let mut framebuffer = DirtyFramebuffer::new(&mut display);
+    framebuffer.flush().unwrap();
+
+    // Like the terminal backend, which hands the whole screen over on each flush
+    let area = framebuffer.bounding_box();
+    framebuffer.fill_contiguous(&area, (0..240 * 320).map(|_| Rgb565::BLACK)).unwrap();
+    assert_eq!(framebuffer.flush().unwrap(), 0);
+
+    framebuffer.fill_contiguous(&area, (0..240 * 320).map(|index| match index {
+        0 => Rgb565::WHITE,
+        _ => Rgb565::BLACK,
+    })).unwrap();
+    assert_eq!(framebuffer.flush().unwrap(), 1);
+}
@@ -0,0 +1,84 @@
+//! Regression tests replaying touch recordings taken with the `record-touches` feature.
+//!
+//! Recordings live in `tests/recordings/`, copy `touches.jsonl` from the SD card to add one.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use esp32_phone_os::drivers::touch::{TouchEvent, TouchPoint};
+use esp32_phone_os::phone::{Phone, WifiState};
+use esp32_phone_os::platform::TouchInput;
+use esp32_phone_os::platform::recording::{parse_recording, TouchRecord, TouchRecorder};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::Terminal;
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MockWifi, HEIGHT, WIDTH};
+
+mod common;
+
+fn recording(name: &str) -> Vec<TouchRecord> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("recordings")
+        .join(name);
+
+    parse_recording(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn replay(name: &str) -> (Phone, Terminal<TestBackend>) {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    phone.replay(&mut terminal, recording(name)).unwrap();
+
+    (phone, terminal)
+}
+
+#[test]
+fn wifi_go_back() {
+    let (phone, _) = replay("wifi_go_back.jsonl");
+
+    assert!(matches!(phone.state, PhoneState::Homepage));
+}
+
+#[test]
+fn tap_connect_twice() {
+    let (phone, terminal) = replay("tap_connect_twice.jsonl");
+
+    // The second tap lands on the homepage, where the display settings now are
+    let PhoneState::InApp(index) = phone.state else {
+        panic!("No app opened");
+    };
+    assert_eq!(phone.apps[index].app_name(), "Display settings");
+    assert!(phone.phone_data.keyboard.is_none());
+    assert!(phone.phone_data.wifi_state == WifiState::Connected(String::from("Home")));
+
+    let configuration = phone.phone_data.wifi.as_ref().unwrap().lock().unwrap().get_configuration().unwrap();
+    assert_eq!(configuration.as_client_conf_ref().unwrap().password.as_str(), "pw");
+
+    let buffer = terminal.backend().buffer();
+    let state_bar: String = (0..WIDTH).map(|x| buffer[(x, 0)].symbol()).collect();
+    assert!(state_bar.ends_with("Home"));
+}
+
+/// Always touched in the middle of the screen
+struct Pressed;
+
+impl TouchInput for Pressed {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        Ok(vec![TouchPoint { x: 120, y: 160, id: 0, event: Some(TouchEvent::Press) }])
+    }
+}
+
+#[test]
+fn recording_errors_do_not_lose_touches() {
+    // No SD card
+    let mut recorder = TouchRecorder::new(Pressed, Path::new("/nonexistent/touches.jsonl"));
+    assert_eq!(recorder.read_touches().unwrap().len(), 1);
+
+    // Card full
+    let mut recorder = TouchRecorder::new(Pressed, Path::new("/dev/full"));
+    assert_eq!(recorder.read_touches().unwrap().len(), 1);
+    assert_eq!(recorder.read_touches().unwrap().len(), 1);
+}
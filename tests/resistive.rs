@@ -0,0 +1,70 @@
+use esp32_phone_os::drivers::resistive::{ResistiveConfig, ResistiveFilter, ResistiveSample};
+use esp32_phone_os::drivers::touch::TouchEvent;
+
+fn sample(x: u16, y: u16, z: u16) -> ResistiveSample {
+    ResistiveSample { x, y, z }
+}
+
+#[test]
+fn light_touch_is_ignored() {
+    let mut filter = ResistiveFilter::new(ResistiveConfig::default());
+
+    assert!(filter.update(&[sample(2048, 2048, 500); 5]).is_empty());
+}
+
+#[test]
+fn press_move_release() {
+    let mut filter = ResistiveFilter::new(ResistiveConfig::default());
+
+    let touches = filter.update(&[sample(2048, 1024, 800); 5]);
+    assert_eq!(touches.len(), 1);
+    assert_eq!(touches[0].event, Some(TouchEvent::Press));
+    assert_eq!((touches[0].x, touches[0].y), (120, 80));
+
+    // Still down between both thresholds
+    let touches = filter.update(&[sample(2048, 1024, 500); 5]);
+    assert_eq!(touches[0].event, Some(TouchEvent::Move));
+
+    let touches = filter.update(&[sample(3072, 2048, 800); 5]);
+    assert_eq!(touches[0].event, Some(TouchEvent::Move));
+    assert_eq!((touches[0].x, touches[0].y), (180, 160));
+
+    // Released once, where the finger was last seen
+    let touches = filter.update(&[sample(2048, 1024, 100); 5]);
+    assert_eq!(touches.len(), 1);
+    assert_eq!(touches[0].event, Some(TouchEvent::Release));
+    assert_eq!((touches[0].x, touches[0].y), (180, 160));
+
+    assert!(filter.update(&[sample(2048, 1024, 100); 5]).is_empty());
+}
+
+#[test]
+fn zero_thresholds_without_samples() {
+    let mut filter = ResistiveFilter::new(ResistiveConfig {
+        press_threshold: 0,
+        release_threshold: 0,
+        ..ResistiveConfig::default()
+    });
+
+    assert!(filter.update(&[]).is_empty());
+
+    filter.update(&[sample(2048, 1024, 800); 5]);
+    assert_eq!(filter.update(&[])[0].event, Some(TouchEvent::Release));
+}
+
+#[test]
+fn noisy_samples_are_averaged() {
+    let mut filter = ResistiveFilter::new(ResistiveConfig::default());
+
+    let touches = filter.update(&[
+        sample(2000, 1000, 800),
+        sample(4000, 10, 800),
+        sample(2048, 1024, 800),
+        sample(2096, 1048, 800),
+        sample(10, 4000, 800),
+        // Finger lifting, dropped
+        sample(0, 4095, 100),
+    ]);
+
+    assert_eq!((touches[0].x, touches[0].y), (120, 80));
+}
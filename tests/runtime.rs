@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use esp32_phone_os::apps::wifi::WifiEvent;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::phone::Phone;
+use esp32_phone_os::runtime::{block_on, unblock, Runtime, UNBLOCK_WORKERS};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::Terminal;
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MockWifi, HEIGHT, WIDTH};
+
+mod common;
+
+#[test]
+fn spawned_tasks_run_in_the_background() {
+    let runtime = Runtime::new();
+    let results = Arc::new(Mutex::new(vec![]));
+
+    for index in 0..4 {
+        let results = results.clone();
+        runtime.spawn(async move {
+            let square = unblock(move || index * index).await.unwrap();
+            results.lock().unwrap().push(square);
+        });
+    }
+
+    runtime.wait_idle();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    assert_eq!(results, vec![0, 1, 4, 9]);
+}
+
+#[test]
+fn block_on_waits_for_blocking_calls() {
+    assert_eq!(block_on(unblock(|| "done")).unwrap(), "done");
+}
+
+#[test]
+fn blocking_calls_share_a_few_threads() {
+    let threads: HashSet<_> = (0..16)
+        .map(|_| block_on(unblock(|| thread::current().id())).unwrap())
+        .collect();
+
+    assert!(threads.len() <= UNBLOCK_WORKERS);
+    assert!(!threads.contains(&thread::current().id()));
+}
+
+#[test]
+fn a_panicking_task_does_not_stop_the_runtime() {
+    let runtime = Runtime::new();
+    let results = Arc::new(Mutex::new(vec![]));
+
+    runtime.spawn(async { panic!("Task failed") });
+    runtime.wait_idle();
+
+    let task_results = results.clone();
+    runtime.spawn(async move {
+        task_results.lock().unwrap().push("done");
+    });
+    runtime.wait_idle();
+
+    assert_eq!(*results.lock().unwrap(), vec!["done"]);
+}
+
+#[test]
+fn a_panicking_blocking_call_returns_an_error() {
+    for _ in 0..UNBLOCK_WORKERS + 1 {
+        assert!(block_on(unblock(|| panic!("Driver failed"))).is_err());
+    }
+
+    assert_eq!(block_on(unblock(|| "done")).unwrap(), "done");
+}
+
+#[test]
+fn wifi_scan_does_not_block_init() {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+    phone.state = PhoneState::InApp(0);
+
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+
+    phone.step(&mut terminal, &mut current_events, None).unwrap();
+    assert!(screen_text(&terminal).contains("Scanning..."));
+
+    // The scan result comes back through the event queue
+    phone.phone_data.runtime.wait_idle();
+    let event = phone.events.try_recv().unwrap();
+    phone.step(&mut terminal, &mut current_events, Some(event)).unwrap();
+
+    let text = screen_text(&terminal);
+    assert!(text.contains("Access points"));
+    assert!(text.contains("Home"));
+}
+
+#[test]
+fn entering_the_wifi_app_scans_only_once() {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.init().unwrap();
+
+    // During the scan started by `init`
+    phone.set_state(PhoneState::InApp(0)).unwrap();
+    assert_eq!(scans(&mut phone), 1);
+
+    // Right after it
+    phone.set_state(PhoneState::Homepage).unwrap();
+    phone.set_state(PhoneState::InApp(0)).unwrap();
+    assert_eq!(scans(&mut phone), 0);
+}
+
+/// Scan results posted since the last call, handled by the phone
+fn scans(phone: &mut Phone) -> usize {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    let mut scans = 0;
+
+    phone.phone_data.runtime.wait_idle();
+
+    while let Ok(event) = phone.events.try_recv() {
+        if let PhoneEvent::App(event) = &event {
+            if let Some(WifiEvent::Scanned(_)) = event.as_any().downcast_ref() {
+                scans += 1;
+            }
+        }
+
+        phone.step(&mut terminal, &mut current_events, Some(event)).unwrap();
+    }
+
+    scans
+}
+
+fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .map(|position| buffer[position].symbol())
+        .collect()
+}
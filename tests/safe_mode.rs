@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use esp32_phone_os::drivers::touch::{TouchEvent, TouchPoint};
+use esp32_phone_os::events::CoreEvent;
+use esp32_phone_os::navigation::DeepLink;
+use esp32_phone_os::phone::Phone;
+use esp32_phone_os::platform::{Storage, TouchInput};
+use esp32_phone_os::state::PhoneState;
+use common::{phone, MockWifi, TempStorage};
+
+mod common;
+
+/// Touched for the given number of reads, released afterwards
+struct HeldTouch(VecDeque<bool>);
+
+impl HeldTouch {
+    fn new(reads: usize) -> Self {
+        HeldTouch((0..reads).map(|_| true).collect())
+    }
+}
+
+impl TouchInput for HeldTouch {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        match self.0.pop_front().unwrap_or(false) {
+            true => Ok(vec![TouchPoint { x: 120, y: 160, id: 0, event: Some(TouchEvent::Press) }]),
+            false => Ok(vec![]),
+        }
+    }
+}
+
+fn phone_with_wifi(storage: TempStorage) -> Phone {
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.phone_data.storage = Some(Box::new(storage));
+    phone
+}
+
+#[test]
+fn a_failing_app_does_not_stop_the_boot() {
+    let storage = TempStorage::new();
+    storage.write(Path::new("wifis.dat"), b"{").unwrap();
+
+    let mut phone = phone_with_wifi(storage);
+    phone.init().unwrap();
+
+    assert!(phone.is_disabled(0));
+    assert!(!phone.is_disabled(1));
+
+    // Opening it tells why, without leaving the homepage
+    phone.set_state(PhoneState::InApp(0)).unwrap();
+    assert_eq!(phone.state, PhoneState::Homepage);
+    assert_eq!(phone.phone_data.dialogs[0].title, "WiFi settings could not start");
+    assert_eq!(&phone.phone_data.dialogs[0].message, phone.disabled_apps.get("WiFi settings").unwrap());
+
+    // Deep links do not reach it either
+    phone.dispatch_event(&CoreEvent::Open(DeepLink::new("WiFi settings", ()))).unwrap();
+    assert_eq!(phone.state, PhoneState::Homepage);
+
+    phone.set_state(PhoneState::InApp(2)).unwrap();
+    assert_eq!(phone.state, PhoneState::InApp(2));
+}
+
+#[test]
+fn safe_mode_starts_only_the_core_apps() {
+    let mut phone = phone_with_wifi(TempStorage::new());
+    phone.safe_mode = true;
+    phone.init().unwrap();
+
+    assert_eq!(phone.disabled_apps.get("WiFi settings").unwrap(), "Not started in safe mode");
+    assert!(!phone.is_disabled(1));
+    assert!(!phone.is_disabled(2));
+
+    // The WiFi app did not even scan
+    phone.phone_data.runtime.wait_idle();
+    assert!(phone.events.try_recv().is_err());
+}
+
+#[test]
+fn safe_mode_needs_a_held_touch() {
+    let mut phone = phone();
+    phone.detect_safe_mode(&mut HeldTouch::new(0));
+    assert!(!phone.safe_mode);
+
+    // A tap is not enough
+    phone.detect_safe_mode(&mut HeldTouch::new(2));
+    assert!(!phone.safe_mode);
+
+    phone.detect_safe_mode(&mut HeldTouch::new(10));
+    assert!(phone.safe_mode);
+}
@@ -0,0 +1,243 @@
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+use async_trait::async_trait;
+use esp32_phone_os::apps::app::{App, AppHandler, AppImpl};
+use esp32_phone_os::events::EventType;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::phone::{Phone, PhoneData};
+use esp32_phone_os::runtime::block_on;
+use esp32_phone_os::services::{FileWatcher, Poller, Services, Timer, MAX_POLL_DURATION, MAX_SERVICES_PER_APP};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Frame, Rect};
+use common::phone;
+
+mod common;
+
+const PERIOD: Duration = Duration::from_millis(100);
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tick(u32);
+
+/// Runs on the services of the app it is sent to
+#[derive(Debug)]
+struct Run(fn(&mut Services) -> anyhow::Result<()>);
+
+/// "Clock", or "Other" to check that apps have their own services
+struct ServiceApp<const OTHER: bool>;
+
+#[async_trait(?Send)]
+impl<const OTHER: bool> AppHandler for ServiceApp<OTHER> {
+    type Event = Run;
+
+    fn new() -> Self where Self: Sized {
+        ServiceApp
+    }
+
+    fn app_name(&self) -> &'static str {
+        match OTHER {
+            true => "Other",
+            false => "Clock",
+        }
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        Ok(EventType::default())
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &Run) -> anyhow::Result<Option<PhoneState>> {
+        (event.0)(&mut phone_data.services)?;
+        Ok(None)
+    }
+}
+
+/// The clock is the first app, the other one the second
+fn phone_with_apps() -> Phone {
+    let mut phone = phone();
+    phone.apps = vec![AppImpl::<ServiceApp<false>>::new_boxed(), AppImpl::<ServiceApp<true>>::new_boxed()];
+    phone
+}
+
+fn run(phone: &mut Phone, index: usize, function: fn(&mut Services) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    block_on(phone.apps[index].handle_event(&mut phone.phone_data, &Run(function)))?;
+    Ok(())
+}
+
+fn tick(event: PhoneEvent) -> Tick {
+    match event {
+        PhoneEvent::App(event) => event.as_any().downcast_ref::<Tick>().unwrap().clone(),
+        event => panic!("Unexpected {:?}", event),
+    }
+}
+
+fn start_ticking(services: &mut Services) -> anyhow::Result<()> {
+    services.start("tick", Timer { period: PERIOD, event: Tick(1) })
+}
+
+fn watched_path() -> PathBuf {
+    std::env::temp_dir().join(format!("phone-os-watched-{}", std::process::id()))
+}
+
+fn assert_no_more_ticks(phone: &Phone) {
+    // Drops a poll that may have been running during the stop
+    sleep(PERIOD * 2);
+    while phone.events.try_recv().is_ok() {}
+
+    sleep(PERIOD * 3);
+    assert!(phone.events.try_recv().is_err());
+}
+
+#[test]
+fn timer_keeps_posting() {
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, start_ticking).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(1));
+    }
+}
+
+#[test]
+fn poller_posts_only_its_events() {
+    let mut phone = phone_with_apps();
+
+    run(&mut phone, 0, |services| {
+        let mut polls = 0;
+
+        services.start("poll", Poller {
+            period: PERIOD,
+            poll: move || {
+                polls += 1;
+                Ok((polls % 2 == 0).then_some(Tick(polls)))
+            },
+        })
+    }).unwrap();
+
+    assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(2));
+    assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(4));
+}
+
+#[test]
+fn file_watcher_notices_changes() {
+    std::fs::write(watched_path(), "before").unwrap();
+
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, |services| services.start("watch", FileWatcher::new(watched_path(), PERIOD, Tick(0)))).unwrap();
+
+    // Lets the first poll record the file
+    sleep(PERIOD * 3);
+    assert!(phone.events.try_recv().is_err());
+
+    std::fs::remove_file(watched_path()).unwrap();
+    assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(0));
+}
+
+#[test]
+fn stopped_services_stop_posting() {
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, start_ticking).unwrap();
+    phone.events.recv_timeout(TIMEOUT).unwrap();
+
+    run(&mut phone, 0, |services| {
+        services.stop_all();
+        Ok(())
+    }).unwrap();
+    assert_eq!(phone.phone_data.services.running("Clock").count(), 0);
+
+    assert_no_more_ticks(&phone);
+}
+
+#[test]
+fn suspended_apps_lose_their_services() {
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, start_ticking).unwrap();
+    phone.events.recv_timeout(TIMEOUT).unwrap();
+
+    phone.suspend_background_apps().unwrap();
+    assert_eq!(phone.phone_data.services.running("Clock").count(), 0);
+
+    assert_no_more_ticks(&phone);
+}
+
+#[test]
+fn services_are_capped_per_app() {
+    let mut phone = phone_with_apps();
+
+    run(&mut phone, 0, |services| {
+        for name in ["a", "b", "c", "d"] {
+            services.start(name, Timer { period: Duration::from_secs(60), event: Tick(0) })?;
+        }
+        Ok(())
+    }).unwrap();
+    assert_eq!(phone.phone_data.services.running("Clock").count(), MAX_SERVICES_PER_APP);
+
+    assert!(run(&mut phone, 0, |services| services.start("e", Timer { period: Duration::from_secs(60), event: Tick(0) })).is_err());
+    // Replacing a service does not count twice
+    assert!(run(&mut phone, 0, |services| services.start("a", Timer { period: Duration::from_secs(30), event: Tick(0) })).is_ok());
+    // Other apps have their own share
+    assert!(run(&mut phone, 1, |services| services.start("a", Timer { period: Duration::from_secs(60), event: Tick(0) })).is_ok());
+    assert_eq!(phone.phone_data.services.running("Other").count(), 1);
+}
+
+#[test]
+fn refused_services_do_not_replace_the_running_one() {
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, start_ticking).unwrap();
+
+    // Busy loops are refused
+    assert!(run(&mut phone, 0, |services| services.start("tick", Timer { period: Duration::ZERO, event: Tick(2) })).is_err());
+
+    assert_eq!(phone.phone_data.services.running("Clock").collect::<Vec<_>>(), ["tick"]);
+    assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(1));
+}
+
+#[test]
+fn a_panicking_service_does_not_stop_the_others() {
+    let mut phone = phone_with_apps();
+    run(&mut phone, 0, start_ticking).unwrap();
+
+    run(&mut phone, 1, |services| {
+        services.start("broken", Poller {
+            period: PERIOD,
+            poll: || -> anyhow::Result<Option<Tick>> { panic!("Broken service") },
+        })
+    }).unwrap();
+
+    sleep(PERIOD * 3);
+    assert_eq!(phone.phone_data.services.running("Other").count(), 0);
+
+    for _ in 0..3 {
+        assert_eq!(tick(phone.events.recv_timeout(TIMEOUT).unwrap()), Tick(1));
+    }
+}
+
+#[test]
+fn services_exceeding_the_poll_duration_are_stopped() {
+    let mut phone = phone_with_apps();
+
+    run(&mut phone, 1, |services| {
+        services.start("slow", Poller {
+            period: PERIOD,
+            poll: || {
+                sleep(MAX_POLL_DURATION + PERIOD);
+                Ok(Some(Tick(2)))
+            },
+        })
+    }).unwrap();
+
+    sleep(MAX_POLL_DURATION + PERIOD * 4);
+    assert_eq!(phone.phone_data.services.running("Other").count(), 0);
+    // Its event came too late to be posted
+    assert!(phone.events.try_recv().is_err());
+}
+
+#[test]
+fn services_are_started_by_apps() {
+    let mut phone = phone();
+    assert!(start_ticking(&mut phone.phone_data.services).is_err());
+}
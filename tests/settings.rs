@@ -0,0 +1,306 @@
+use std::path::Path;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use esp32_phone_os::apps::app::{AppHandler, AppImpl, App};
+use esp32_phone_os::apps::display::{DisplayEvent, ORIENTATION};
+use esp32_phone_os::drivers::display::ScreenOrientation;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::apps::storage::StorageEvent;
+use esp32_phone_os::events::{CoreEvent, EventType};
+use esp32_phone_os::phone::{Phone, PhoneData};
+use esp32_phone_os::platform::{SettingsStore, Storage};
+use esp32_phone_os::settings::{Setting, SettingChange, SETTINGS_EXPORT_PATH};
+use esp32_phone_os::state::PhoneState;
+use esp32_phone_os::ui::widgets::dialog::DialogEvent;
+use mousefood::prelude::{Frame, Rect, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MemorySettings, TempStorage, HEIGHT, WIDTH};
+
+mod common;
+
+const VOLUME: Setting<u8> = Setting::new("Sound", "volume", 5);
+const RINGTONE: Setting<String> = Setting::new("Sound", "ringtone", String::new());
+
+/// Orientation read by the sound app, see `SoundEvent::ReadOrientation`
+static READ_ORIENTATION: Mutex<Option<ScreenOrientation>> = Mutex::new(None);
+
+/// Keys of the changes followed by the sound app, see `AppHandler::on_setting_changed`
+static FOLLOWED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Third-party app owning the "Sound" settings, which fails to follow their changes if `FAILING`
+struct Sound<const FAILING: bool>;
+
+#[derive(Debug)]
+enum SoundEvent {
+    Volume(u8),
+    Ringtone(&'static str),
+    ResetVolume,
+    /// Stored with another type than `VOLUME`
+    LoudVolume,
+    Orientation(ScreenOrientation),
+    ReadOrientation,
+    Export,
+}
+
+#[async_trait(?Send)]
+impl<const FAILING: bool> AppHandler for Sound<FAILING> {
+    type Event = SoundEvent;
+
+    fn new() -> Self where Self: Sized {
+        Sound
+    }
+
+    fn app_name(&self) -> &'static str {
+        "Sound"
+    }
+
+    async fn init(&mut self, _phone_data: &mut PhoneData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn render(&mut self, _phone_data: &mut PhoneData, _frame: &mut Frame, _area: Rect) -> anyhow::Result<EventType> {
+        Ok(EventType::default())
+    }
+
+    async fn handle_event(&mut self, phone_data: &mut PhoneData, event: &SoundEvent) -> anyhow::Result<Option<PhoneState>> {
+        let settings = &mut phone_data.settings;
+
+        match event {
+            SoundEvent::Volume(volume) => settings.set(&VOLUME, *volume)?,
+            SoundEvent::Ringtone(ringtone) => settings.set(&RINGTONE, String::from(*ringtone))?,
+            SoundEvent::ResetVolume => settings.reset(&VOLUME)?,
+            SoundEvent::LoudVolume => settings.set_value("Sound", "volume", &"loud")?,
+            SoundEvent::Orientation(orientation) => settings.set(&ORIENTATION, *orientation)?,
+            SoundEvent::ReadOrientation => *READ_ORIENTATION.lock().unwrap() = Some(settings.get(&ORIENTATION)),
+            SoundEvent::Export => settings.export(&TempStorage::new())?,
+        }
+
+        Ok(None)
+    }
+
+    async fn on_setting_changed(&mut self, _phone_data: &mut PhoneData, change: &SettingChange) -> anyhow::Result<()> {
+        match FAILING {
+            true => Err(anyhow::anyhow!("Broken")),
+            false => {
+                FOLLOWED.lock().unwrap().push(change.key.clone());
+                Ok(())
+            },
+        }
+    }
+}
+
+/// With the sound app installed, after the default ones
+fn sound_phone() -> Phone {
+    let mut phone = phone();
+    phone.apps.push(AppImpl::<Sound<false>>::new_boxed());
+    phone
+}
+
+/// Handled by the sound app, as it would be in the background
+fn sound(phone: &mut Phone, event: SoundEvent) -> anyhow::Result<()> {
+    phone.handle_posted_event(&event).map(|_| ())
+}
+
+/// NVS partition which cannot be written, e.g. once full
+struct FullSettings;
+
+impl SettingsStore for FullSettings {
+    fn load(&self) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn save(&mut self, _content: &str) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("No space left"))
+    }
+}
+
+fn setting_changes(phone: &Phone) -> Vec<SettingChange> {
+    phone.events.try_iter()
+        .filter_map(|event| match event {
+            PhoneEvent::SettingChanged(change) => Some(change),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn settings_are_typed_with_defaults() {
+    let mut phone = sound_phone();
+
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+
+    sound(&mut phone, SoundEvent::Volume(8)).unwrap();
+    sound(&mut phone, SoundEvent::Ringtone("Bells")).unwrap();
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 8);
+    assert_eq!(phone.phone_data.settings.get(&RINGTONE), "Bells");
+
+    // Setting the same value again is not a change
+    sound(&mut phone, SoundEvent::Volume(8)).unwrap();
+    assert_eq!(setting_changes(&phone).len(), 2);
+
+    sound(&mut phone, SoundEvent::ResetVolume).unwrap();
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+    assert!(setting_changes(&phone)[0].is(&VOLUME));
+}
+
+#[test]
+fn a_value_of_another_type_reads_as_the_default() {
+    let mut phone = sound_phone();
+    sound(&mut phone, SoundEvent::LoudVolume).unwrap();
+
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+}
+
+#[test]
+fn settings_survive_a_reboot() {
+    let store = MemorySettings::default();
+
+    let mut phone = phone();
+    phone.phone_data.settings.load(Box::new(store.clone())).unwrap();
+    phone.init().unwrap();
+    phone.set_state(PhoneState::InApp(2)).unwrap();
+    phone.dispatch_event(&DisplayEvent::SetOrientation(ScreenOrientation::Landscape)).unwrap();
+    assert!(store.0.lock().unwrap().is_some());
+
+    let mut rebooted = common::phone();
+    rebooted.phone_data.settings.load(Box::new(store)).unwrap();
+    rebooted.init().unwrap();
+
+    assert_eq!(rebooted.phone_data.screen.orientation, ScreenOrientation::Landscape);
+}
+
+#[test]
+fn apps_follow_changed_settings() {
+    let card = TempStorage::new();
+    card.write(Path::new(SETTINGS_EXPORT_PATH), br#"{"Display settings":{"orientation":"Landscape"}}"#).unwrap();
+
+    let mut phone = phone();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.phone_data.storage = Some(Box::new(card));
+    phone.init().unwrap();
+
+    // Changed by the storage settings, on behalf of the display settings
+    phone.handle_posted_event(&StorageEvent::ImportSettings).unwrap();
+    assert_eq!(phone.phone_data.screen.orientation, ScreenOrientation::Portrait);
+
+    let change = setting_changes(&phone).remove(0);
+    phone.step(&mut terminal, &mut current_events, Some(PhoneEvent::SettingChanged(change))).unwrap();
+    assert_eq!(phone.phone_data.screen.orientation, ScreenOrientation::Landscape);
+}
+
+#[test]
+fn settings_are_backed_up_on_the_sd_card() {
+    let storage = TempStorage::new();
+
+    let mut phone = sound_phone();
+    sound(&mut phone, SoundEvent::Volume(8)).unwrap();
+    phone.phone_data.settings.export(&storage).unwrap();
+    assert!(storage.read_to_string(Path::new(SETTINGS_EXPORT_PATH)).unwrap().unwrap().contains("volume"));
+
+    let mut other = sound_phone();
+    other.phone_data.storage = Some(Box::new(TempStorage::new()));
+    other.handle_posted_event(&StorageEvent::ImportSettings).unwrap();
+    assert_eq!(other.phone_data.toast.as_ref().unwrap().text, "No settings backup");
+
+    sound(&mut other, SoundEvent::Ringtone("Bells")).unwrap();
+    setting_changes(&other);
+
+    other.phone_data.storage = Some(Box::new(storage));
+    other.handle_posted_event(&StorageEvent::ImportSettings).unwrap();
+    assert_eq!(other.phone_data.toast.as_ref().unwrap().text, "Settings imported");
+    assert_eq!(other.phone_data.settings.get(&VOLUME), 8);
+    assert_eq!(other.phone_data.settings.get(&RINGTONE), "");
+
+    // Both the imported and the dropped values changed
+    let changes = setting_changes(&other);
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().any(|change| change.is(&VOLUME)));
+    assert!(changes.iter().any(|change| change.is(&RINGTONE)));
+}
+
+#[test]
+fn unreadable_settings_fall_back_to_the_defaults() {
+    let store = MemorySettings::default();
+    *store.0.lock().unwrap() = Some(String::from("{\"Sound\":"));
+
+    let mut phone = sound_phone();
+    phone.phone_data.settings.load(Box::new(store.clone())).unwrap();
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+
+    // Still saved to the store
+    sound(&mut phone, SoundEvent::Volume(8)).unwrap();
+    assert!(store.0.lock().unwrap().as_ref().unwrap().contains("volume"));
+}
+
+#[test]
+fn settings_which_cannot_be_saved_are_kept_unchanged() {
+    let mut phone = sound_phone();
+    phone.phone_data.settings.load(Box::new(FullSettings)).unwrap();
+
+    assert!(sound(&mut phone, SoundEvent::Volume(8)).is_err());
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+    assert!(setting_changes(&phone).is_empty());
+}
+
+#[test]
+fn settings_are_backed_up_from_the_storage_settings() {
+    let card = TempStorage::new();
+
+    let mut phone = sound_phone();
+    phone.phone_data.storage = Some(Box::new(card.clone()));
+    phone.init().unwrap();
+    phone.set_state(PhoneState::InApp(3)).unwrap();
+
+    phone.dispatch_event(&StorageEvent::ImportSettings).unwrap();
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "No settings backup");
+
+    sound(&mut phone, SoundEvent::Volume(8)).unwrap();
+    phone.dispatch_event(&StorageEvent::ExportSettings).unwrap();
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "Settings exported");
+    assert!(card.read_to_string(Path::new(SETTINGS_EXPORT_PATH)).unwrap().unwrap().contains("volume"));
+
+    sound(&mut phone, SoundEvent::ResetVolume).unwrap();
+    phone.dispatch_event(&StorageEvent::AskImport).unwrap();
+    assert_eq!(phone.phone_data.dialogs[0].title, "Import settings?");
+
+    phone.dispatch_event(&CoreEvent::Dialog(DialogEvent::Confirm)).unwrap();
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 8);
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "Settings imported");
+}
+
+#[test]
+fn setting_changes_reach_their_app_despite_failing_ones() {
+    let mut phone = phone();
+    phone.apps.push(AppImpl::<Sound<true>>::new_boxed());
+    phone.apps.push(AppImpl::<Sound<false>>::new_boxed());
+    phone.init().unwrap();
+
+    // Not delivered to the sound app, only the core apps read the display settings
+    phone.handle_setting_change(&SettingChange { namespace: String::from("Display settings"), key: String::from("orientation") });
+    phone.handle_setting_change(&SettingChange { namespace: String::from("Sound"), key: String::from("volume") });
+
+    assert_eq!(*FOLLOWED.lock().unwrap(), ["volume"]);
+}
+
+#[test]
+fn apps_only_reach_their_own_settings() {
+    let mut phone = sound_phone();
+    phone.init().unwrap();
+    phone.set_state(PhoneState::InApp(2)).unwrap();
+    phone.dispatch_event(&DisplayEvent::SetOrientation(ScreenOrientation::Landscape)).unwrap();
+
+    // Neither changed nor read by another app
+    assert!(sound(&mut phone, SoundEvent::Orientation(ScreenOrientation::Portrait)).is_err());
+    assert_eq!(phone.phone_data.settings.get(&ORIENTATION), ScreenOrientation::Landscape);
+
+    sound(&mut phone, SoundEvent::ReadOrientation).unwrap();
+    assert_eq!(*READ_ORIENTATION.lock().unwrap(), Some(ScreenOrientation::Portrait));
+
+    // Nor all at once
+    assert!(sound(&mut phone, SoundEvent::Export).is_err());
+
+    // The phone itself is not an app
+    assert!(phone.phone_data.settings.set(&VOLUME, 8).is_err());
+    assert_eq!(phone.phone_data.settings.get(&VOLUME), 5);
+}
@@ -0,0 +1,436 @@
+//! Golden-buffer snapshots of every screen, rendered on the 40x32 cell grid of the phone.
+//!
+//! Snapshots live in `tests/snapshots/`, run with `UPDATE_SNAPSHOTS=1` to (re)generate them.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use embedded_svc::wifi::AuthMethod;
+use esp32_phone_os::apps::app::AppHandler;
+use esp32_phone_os::apps::calibration::{CalibrationApp, CalibrationAppState};
+use esp32_phone_os::apps::display::DisplayApp;
+use esp32_phone_os::apps::storage::StorageApp;
+use esp32_phone_os::apps::wifi::{WifiApp, WifiAppState};
+use esp32_phone_os::crash::CrashRecord;
+use esp32_phone_os::drivers::display::{DisplayModel, Screen, ScreenOrientation};
+use esp32_phone_os::notifications::{Notification, NotificationEvent, Priority};
+use esp32_phone_os::phone::{Detection, TouchHealth, WifiState};
+use esp32_phone_os::state::PhoneState;
+use esp32_phone_os::ui::widgets::dialog::Dialog;
+use esp32_phone_os::ui::widgets::keyboard::{KeyboardEvent, KeyboardLayout, SymbolLevel};
+use esp32_phone_os::ui::widgets::toast::Toast;
+use esp32_phone_os::events::gestures::{Gesture, SwipeDirection};
+use mousefood::prelude::{Buffer, Frame, Position, Rect, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{access_point, phone, TempStorage, HEIGHT, WIDTH};
+
+mod common;
+
+fn new_wifi_app(state: WifiAppState) -> WifiApp {
+    let mut app = WifiApp::new();
+    app.state = state;
+    app.access_points = vec![
+        access_point("Wokwi-GUEST", AuthMethod::None),
+        access_point("Home", AuthMethod::WPA2Personal),
+    ];
+
+    app
+}
+
+fn render<F: FnOnce(&mut Frame)>(render_fn: F) -> Buffer {
+    render_on(Screen::default(), render_fn)
+}
+
+fn render_on<F: FnOnce(&mut Frame)>(screen: Screen, render_fn: F) -> Buffer {
+    let (width, height) = screen.size();
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(render_fn).unwrap();
+
+    terminal.backend().buffer().clone()
+}
+
+fn buffer_to_string(buffer: &Buffer) -> String {
+    let mut lines = String::new();
+
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            lines.push_str(buffer[(x, y)].symbol());
+        }
+        lines.push('\n');
+    }
+
+    lines
+}
+
+fn assert_snapshot(name: &str, buffer: &Buffer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+
+    let actual = buffer_to_string(buffer);
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing snapshot {}, run with UPDATE_SNAPSHOTS=1", path.display()));
+
+    assert_eq!(actual, expected, "Snapshot {name} differs");
+}
+
+#[test]
+fn homepage() {
+    let mut phone = phone();
+    let buffer = render(|frame| phone.render_homepage(frame));
+
+    assert_snapshot("homepage", &buffer);
+}
+
+#[test]
+fn app_list() {
+    let mut phone = phone();
+    let buffer = render(|frame| {
+        phone.render_app_list(frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+
+    assert_snapshot("app_list", &buffer);
+}
+
+#[test]
+fn state_bar() {
+    let states = [
+        ("state_bar_not_initialized", WifiState::NotInitialized),
+        ("state_bar_not_connected", WifiState::NotConnected),
+        ("state_bar_connecting", WifiState::Connecting),
+        ("state_bar_connected", WifiState::Connected(String::from("Wokwi-GUEST"))),
+    ];
+
+    for (name, wifi_state) in states {
+        let mut phone = phone();
+        phone.phone_data.wifi_state = wifi_state;
+
+        let buffer = render(|frame| phone.render_state_bar(frame));
+
+        assert_snapshot(name, &buffer);
+    }
+}
+
+#[test]
+fn state_bar_without_hardware() {
+    let mut phone = phone();
+    phone.phone_data.sd_card = Detection::Missing;
+    phone.phone_data.wifi_state = WifiState::Unavailable;
+
+    let buffer = render(|frame| phone.render_state_bar(frame));
+
+    assert_snapshot("state_bar_no_hardware", &buffer);
+}
+
+#[test]
+fn state_bar_in_app() {
+    let mut phone = phone();
+    phone.state = PhoneState::InApp(0);
+
+    let buffer = render(|frame| phone.render_state_bar(frame));
+
+    assert_snapshot("state_bar_in_app", &buffer);
+}
+
+#[test]
+fn state_bar_touch_health() {
+    let states = [
+        ("state_bar_touch_degraded", TouchHealth::Degraded),
+        ("state_bar_touch_unavailable", TouchHealth::Unavailable),
+    ];
+
+    for (name, touch_health) in states {
+        let mut phone = phone();
+        phone.phone_data.touch_health = touch_health;
+
+        let buffer = render(|frame| phone.render_state_bar(frame));
+
+        assert_snapshot(name, &buffer);
+    }
+}
+
+#[test]
+fn homepage_draw() {
+    let mut phone = phone();
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+
+    assert_snapshot("homepage_draw", &buffer);
+}
+
+#[test]
+fn notifications() {
+    let mut phone = phone();
+    phone.phone_data.notify(Notification::new("Clock", "Alarm", "Wake up", Priority::High));
+    phone.phone_data.notify(Notification::new("WiFi settings", "Connected", "Home", Priority::Normal));
+    phone.phone_data.notify(Notification::new("Updates", "Nothing new", "Checked at 12:00", Priority::Low));
+
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("state_bar_unread", &buffer);
+
+    phone.handle_notification_event(&NotificationEvent::OpenShade).unwrap();
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("notification_shade", &buffer);
+
+    phone.handle_notification_event(&NotificationEvent::Clear).unwrap();
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("notification_shade_empty", &buffer);
+}
+
+#[test]
+fn dialogs() {
+    let mut phone = phone();
+    phone.phone_data.show_toast(Toast::error("Disk full"));
+
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("toast", &buffer);
+
+    phone.phone_data.toast = None;
+    phone.phone_data.show_dialog(Dialog::confirm("Forget network", "Forget Home and its password?", ()));
+    phone.phone_data.show_dialog(Dialog::alert("WiFi settings failed", "No access point named Home"));
+
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("dialog_confirm", &buffer);
+
+    phone.phone_data.dialogs.remove(0);
+    let buffer = render(|frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("dialog_alert", &buffer);
+}
+
+#[test]
+fn crash_screen() {
+    let record = CrashRecord {
+        message: String::from("called `Option::unwrap()` on a `None` value"),
+        location: Some(String::from("src/apps/wifi.rs:120:45")),
+        thread: Some(String::from("main")),
+        uptime_secs: 192,
+        free_heap: Some(41_320),
+    };
+
+    let buffer = render(|frame| {
+        record.render(frame);
+    });
+    assert_snapshot("crash_screen", &buffer);
+}
+
+#[test]
+fn wifi_app() {
+    let states = [
+        ("wifi_scanning", WifiAppState::Scanning),
+        ("wifi_displaying_networks", WifiAppState::DisplayingNetworks),
+    ];
+
+    for (name, state) in states {
+        let mut phone = phone();
+        let mut app = new_wifi_app(state);
+
+        let buffer = render(|frame| {
+            app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+        });
+
+        assert_snapshot(name, &buffer);
+    }
+}
+
+#[test]
+fn wifi_app_scrolled_networks() {
+    let mut phone = phone();
+    let mut app = new_wifi_app(WifiAppState::DisplayingNetworks);
+    app.access_points = (0..12)
+        .map(|index| access_point(&format!("Network {index}"), AuthMethod::WPA2Personal))
+        .collect();
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+    assert_snapshot("wifi_many_networks", &buffer);
+
+    // Drag the list up by two items
+    app.network_list.handle_gesture(&Gesture::Drag { from: Position::new(10, 20), to: Position::new(10, 14) });
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+    assert_snapshot("wifi_many_networks_scrolled", &buffer);
+
+    // Swiping down past the top stops on the first item
+    app.network_list.handle_gesture(&Gesture::Swipe { from: Position::new(10, 10), direction: SwipeDirection::Down });
+    assert_eq!(app.network_list.offset(), 0);
+
+    // A quick drag scrolls as it moves, not once more when it ends with a swipe
+    app.network_list.handle_gesture(&Gesture::Drag { from: Position::new(10, 20), to: Position::new(10, 17) });
+    app.network_list.handle_gesture(&Gesture::Drag { from: Position::new(10, 17), to: Position::new(10, 14) });
+    app.network_list.handle_gesture(&Gesture::Swipe { from: Position::new(10, 20), direction: SwipeDirection::Up });
+    assert_eq!(app.network_list.offset(), 2);
+}
+
+#[test]
+fn wifi_app_typing_password() {
+    let mut phone = phone();
+    let mut app = new_wifi_app(WifiAppState::TypingPassword(access_point("Home", AuthMethod::WPA2Personal)));
+
+    phone.phone_data.display_keyboard(KeyboardLayout::Azerty, true);
+    for letter in "secret".chars() {
+        phone.phone_data.keyboard.as_mut().unwrap().handle_event(&KeyboardEvent::Letter(letter));
+    }
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+        phone.phone_data.keyboard.as_ref().unwrap().render(frame);
+    });
+
+    assert_snapshot("wifi_typing_password", &buffer);
+}
+
+#[test]
+fn calibration_app() {
+    let states = [
+        ("calibration_intro", CalibrationAppState::Intro),
+        ("calibration_target", CalibrationAppState::Target(1)),
+        ("calibration_done", CalibrationAppState::Done),
+    ];
+
+    for (name, state) in states {
+        let mut phone = phone();
+        let mut app = CalibrationApp::new();
+        app.state = state;
+
+        let buffer = render(|frame| {
+            app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+        });
+
+        assert_snapshot(name, &buffer);
+    }
+}
+
+#[test]
+fn landscape() {
+    let mut phone = phone();
+    phone.phone_data.screen = Screen::new(DisplayModel::Ili9341, ScreenOrientation::Landscape);
+
+    let buffer = render_on(phone.phone_data.screen, |frame| {
+        phone.draw(frame).unwrap();
+    });
+    assert_snapshot("landscape_homepage_draw", &buffer);
+
+    phone.state = PhoneState::InApp(0);
+    let mut app = new_wifi_app(WifiAppState::TypingPassword(access_point("Home", AuthMethod::WPA2Personal)));
+    phone.phone_data.display_keyboard(KeyboardLayout::Azerty, true);
+
+    let buffer = render_on(phone.phone_data.screen, |frame| {
+        phone.render_state_bar(frame);
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, frame.area().width, frame.area().height - 2)).unwrap();
+        phone.phone_data.keyboard.as_ref().unwrap().render(frame);
+    });
+    assert_snapshot("landscape_wifi_typing_password", &buffer);
+}
+
+#[test]
+fn square_screen() {
+    let mut phone = phone();
+    phone.phone_data.screen = Screen::new(DisplayModel::St7789Square, ScreenOrientation::Portrait);
+
+    let buffer = render_on(phone.phone_data.screen, |frame| {
+        phone.draw(frame).unwrap();
+    });
+
+    assert_snapshot("square_homepage_draw", &buffer);
+}
+
+#[test]
+fn display_app() {
+    let mut phone = phone();
+    let mut app = DisplayApp::new();
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+
+    assert_snapshot("display_app", &buffer);
+}
+
+#[test]
+fn storage_app() {
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(TempStorage::new()));
+    phone.phone_data.app_usage.lock().unwrap().extend([
+        ("WiFi settings", 1536),
+        ("Touch calibration", 0),
+        ("Notes", 3 * 1024 * 1024),
+    ]);
+    let mut app = StorageApp::new();
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+
+    assert_snapshot("storage_app", &buffer);
+}
+
+#[test]
+fn storage_app_many_apps() {
+    const APP_NAMES: [&str; 10] = ["Alarm", "Browser", "Camera", "Clock", "Contacts", "Maps", "Music", "Notes", "Photos", "Weather"];
+
+    let mut phone = phone();
+    phone.phone_data.storage = Some(Box::new(TempStorage::new()));
+    phone.phone_data.app_usage.lock().unwrap().extend(APP_NAMES.map(|app_name| (app_name, 2048)));
+    let mut app = StorageApp::new();
+
+    // Drag the list up by two items, past the first render which measures it
+    render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+    app.app_list.handle_gesture(&Gesture::Drag { from: Position::new(10, 20), to: Position::new(10, 14) });
+
+    let buffer = render(|frame| {
+        app.render(&mut phone.phone_data, frame, Rect::new(0, 2, WIDTH, HEIGHT - 2)).unwrap();
+    });
+    assert_snapshot("storage_app_many_apps_scrolled", &buffer);
+}
+
+#[test]
+fn keyboard() {
+    let layouts = [
+        ("keyboard_azerty", vec![]),
+        ("keyboard_azerty_maj", vec![KeyboardEvent::Maj]),
+        ("keyboard_symbols_1", vec![KeyboardEvent::Symbols(SymbolLevel::First)]),
+        ("keyboard_symbols_2", vec![KeyboardEvent::Symbols(SymbolLevel::First), KeyboardEvent::Symbols(SymbolLevel::Second)]),
+    ];
+
+    for (name, events) in layouts {
+        let mut phone = phone();
+        phone.phone_data.display_keyboard(KeyboardLayout::Azerty, false);
+
+        let keyboard = phone.phone_data.keyboard.as_mut().unwrap();
+        for event in &events {
+            keyboard.handle_event(event);
+        }
+
+        let buffer = render(|frame| {
+            keyboard.render(frame);
+        });
+
+        assert_snapshot(name, &buffer);
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use esp32_phone_os::events::gestures::Gesture;
+use esp32_phone_os::events::queue::PhoneEvent;
+use esp32_phone_os::notifications::{Notification, Priority, NOTIFICATIONS_PATH};
+use esp32_phone_os::phone::{Detection, Phone, WifiState};
+use esp32_phone_os::platform::{Storage, StorageSlot};
+use esp32_phone_os::state::PhoneState;
+use mousefood::prelude::{Position, Terminal};
+use mousefood::ratatui::backend::TestBackend;
+use common::{phone, MockWifi, TempStorage, HEIGHT, WIDTH};
+
+mod common;
+
+const WIFIS_PATH: &str = "apps/wifi_settings/wifis.dat";
+
+/// Mounts its directory once the card is inserted
+struct TempSlot {
+    card: TempStorage,
+    inserted: Arc<AtomicBool>,
+}
+
+impl StorageSlot for TempSlot {
+    fn mount(&mut self) -> anyhow::Result<Box<dyn Storage + Send>> {
+        match self.inserted.load(Ordering::Relaxed) {
+            true => Ok(Box::new(self.card.clone())),
+            false => Err(anyhow::anyhow!("No card")),
+        }
+    }
+}
+
+fn screen_text(phone: &mut Phone) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+}
+
+/// Starts an attempt on the unblock workers, then picks up its answer
+fn check_again(phone: &mut Phone) {
+    phone.storage_attempt = None;
+    phone.storage_check();
+    phone.phone_data.runtime.wait_idle();
+    phone.storage_check();
+}
+
+#[test]
+fn the_phone_boots_without_an_sd_card_and_mounts_it_once_inserted() {
+    let card = TempStorage::new();
+    card.write(Path::new(NOTIFICATIONS_PATH), br#"{"notifications":[],"next_id":7}"#).unwrap();
+
+    let inserted = Arc::new(AtomicBool::new(false));
+
+    let mut phone = phone();
+    phone.phone_data.wifi = Some(Arc::new(Mutex::new(MockWifi::new())));
+    phone.storage_slot = Some(Box::new(TempSlot { card: card.clone(), inserted: inserted.clone() }));
+    phone.storage_check();
+    phone.init().unwrap();
+
+    assert_eq!(phone.phone_data.sd_card, Detection::Missing);
+    assert!(!card.root().join(WIFIS_PATH).exists());
+    assert!(phone.disabled_apps.is_empty());
+    assert!(screen_text(&mut phone).contains("No SD card"));
+
+    // Kept in memory until then
+    phone.phone_data.notify(Notification::new("Test", "Offline", "No card yet", Priority::Normal));
+
+    inserted.store(true, Ordering::Relaxed);
+    phone.storage_check();
+    assert_eq!(phone.phone_data.sd_card, Detection::Missing, "Attempts are spaced");
+
+    check_again(&mut phone);
+    assert_eq!(phone.phone_data.sd_card, Detection::Detected);
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "SD card inserted");
+    assert!(!screen_text(&mut phone).contains("No SD card"));
+
+    // The WiFi app saved its defaults, which it could not do without the card
+    assert!(card.root().join(WIFIS_PATH).exists());
+
+    let notifications = phone.phone_data.notifications.sorted();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].id, 7);
+}
+
+#[test]
+fn a_removed_sd_card_is_forgotten_until_inserted_again() {
+    let card = TempStorage::new();
+
+    let mut phone = phone();
+    phone.storage_slot = Some(Box::new(TempSlot { card: card.clone(), inserted: Arc::new(AtomicBool::new(true)) }));
+    phone.storage_check();
+    phone.init().unwrap();
+
+    assert_eq!(phone.phone_data.sd_card, Detection::Detected);
+    assert!(phone.crash_storage_root.lock().unwrap().is_some());
+    phone.phone_data.notify(Notification::new("Test", "Saved", "On the card", Priority::Normal));
+
+    check_again(&mut phone);
+    assert_eq!(phone.phone_data.sd_card, Detection::Detected, "Still readable");
+
+    // Set aside, the same card is inserted again below
+    let aside = card.root().with_extension("aside");
+    std::fs::rename(card.root(), &aside).unwrap();
+    check_again(&mut phone);
+
+    assert_eq!(phone.phone_data.sd_card, Detection::Missing);
+    assert!(phone.phone_data.storage.is_none());
+    assert!(phone.crash_storage_root.lock().unwrap().is_none());
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "SD card removed");
+
+    phone.phone_data.notify(Notification::new("Test", "Offline", "No card", Priority::Normal));
+
+    std::fs::rename(&aside, card.root()).unwrap();
+    check_again(&mut phone);
+
+    assert_eq!(phone.phone_data.sd_card, Detection::Detected);
+    assert_eq!(phone.phone_data.toast.as_ref().unwrap().text, "SD card inserted");
+
+    // Only the one posted without the card is added to the saved one
+    let titles: Vec<_> = phone.phone_data.notifications.sorted().iter().map(|notification| notification.title.clone()).collect();
+    assert_eq!(titles.len(), 2);
+    assert!(titles.contains(&String::from("Saved")) && titles.contains(&String::from("Offline")));
+}
+
+#[test]
+fn the_phone_boots_without_wifi_hardware() {
+    let mut phone = phone();
+    phone.phone_data.wifi_hardware = Detection::Missing;
+    phone.init().unwrap();
+    phone.system_check().unwrap();
+
+    assert!(phone.phone_data.wifi_state == WifiState::Unavailable);
+
+    phone.set_state(PhoneState::InApp(0)).unwrap();
+    let screen = screen_text(&mut phone);
+    assert!(screen.contains("No WiFi hardware"));
+    assert!(screen.contains("No WiFi"));
+
+    // Not stuck waiting for a scan which never comes
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    let mut current_events = None;
+    phone.redraw(&mut terminal, &mut current_events).unwrap();
+
+    let go_back = screen.find("← Go back").map(|index| screen[..index].chars().count() as u16).unwrap();
+    let tap = Gesture::Tap(Position::new(go_back % WIDTH, go_back / WIDTH));
+    phone.step(&mut terminal, &mut current_events, Some(PhoneEvent::Gesture(tap))).unwrap();
+
+    assert_eq!(phone.state, PhoneState::Homepage);
+}
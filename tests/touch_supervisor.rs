@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use anyhow::anyhow;
+use esp32_phone_os::drivers::touch::{TouchEvent, TouchPoint};
+use esp32_phone_os::phone::TouchHealth;
+use esp32_phone_os::platform::supervisor::{TouchConfig, TouchSupervisor, MISSED_INTERRUPTS_BEFORE_POLLING, REINIT_AFTER_FAILURES};
+use esp32_phone_os::platform::TouchInput;
+
+/// Plays scripted reads, `false` being an I2C error
+struct FlakyTouch {
+    reads: VecDeque<bool>,
+    reinit_succeeds: bool,
+    reinits: usize,
+}
+
+impl FlakyTouch {
+    fn new(reads: &[bool], reinit_succeeds: bool) -> Self {
+        FlakyTouch {
+            reads: reads.iter().copied().collect(),
+            reinit_succeeds,
+            reinits: 0,
+        }
+    }
+}
+
+impl TouchInput for FlakyTouch {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        match self.reads.pop_front().unwrap_or(true) {
+            true => Ok(vec![TouchPoint { x: 120, y: 160, id: 0, event: Some(TouchEvent::Press) }]),
+            false => Err(anyhow!("I2C timeout")),
+        }
+    }
+
+    fn reinit(&mut self) -> anyhow::Result<()> {
+        self.reinits += 1;
+
+        match self.reinit_succeeds {
+            true => Ok(()),
+            false => Err(anyhow!("FT6206 not found")),
+        }
+    }
+}
+
+/// Interrupt line which never fires, while a finger is always down
+struct UnwiredInterrupt;
+
+impl TouchInput for UnwiredInterrupt {
+    fn read_touches(&mut self) -> anyhow::Result<Vec<TouchPoint>> {
+        Ok(vec![TouchPoint { x: 120, y: 160, id: 0, event: Some(TouchEvent::Press) }])
+    }
+
+    fn has_interrupt(&self) -> bool {
+        true
+    }
+
+    fn wait_for_interrupt(&mut self, _timeout: Duration) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
+
+#[test]
+fn failed_read_is_retried() {
+    let mut supervisor = TouchSupervisor::new(FlakyTouch::new(&[false, true], true), TouchConfig::default());
+
+    assert!(supervisor.read_touches().is_empty());
+    assert_eq!(supervisor.health(), TouchHealth::Degraded);
+
+    assert_eq!(supervisor.read_touches().len(), 1);
+    assert_eq!(supervisor.health(), TouchHealth::Healthy);
+}
+
+#[test]
+fn repeated_failures_reinit_the_controller() {
+    let failures = vec![false; REINIT_AFTER_FAILURES as usize];
+    let mut supervisor = TouchSupervisor::new(FlakyTouch::new(&failures, true), TouchConfig::default());
+
+    for _ in 0..REINIT_AFTER_FAILURES {
+        supervisor.read_touches();
+    }
+
+    assert_eq!(supervisor.health(), TouchHealth::Degraded);
+
+    supervisor.read_touches();
+    assert_eq!(supervisor.health(), TouchHealth::Healthy);
+}
+
+#[test]
+fn failed_reinit_makes_touch_unavailable() {
+    let failures = vec![false; 2 * REINIT_AFTER_FAILURES as usize];
+    let mut supervisor = TouchSupervisor::new(FlakyTouch::new(&failures, false), TouchConfig::default());
+    let healthy_interval = supervisor.poll_interval();
+
+    for _ in 0..REINIT_AFTER_FAILURES {
+        supervisor.read_touches();
+    }
+
+    assert_eq!(supervisor.health(), TouchHealth::Unavailable);
+    assert!(supervisor.poll_interval() > healthy_interval);
+
+    // Keeps retrying until the controller answers again
+    for _ in 0..REINIT_AFTER_FAILURES {
+        supervisor.read_touches();
+    }
+
+    assert_eq!(supervisor.health(), TouchHealth::Unavailable);
+
+    supervisor.read_touches();
+    assert_eq!(supervisor.health(), TouchHealth::Healthy);
+}
+
+#[test]
+fn an_unwired_interrupt_line_falls_back_to_polling() {
+    let mut supervisor = TouchSupervisor::new(UnwiredInterrupt, TouchConfig::default());
+    assert!(!supervisor.is_polling());
+
+    // Touches found once the wait timed out were missed by the interrupt line
+    for _ in 0..MISSED_INTERRUPTS_BEFORE_POLLING {
+        supervisor.wait(false);
+        supervisor.read_touches();
+    }
+
+    assert!(supervisor.is_polling());
+}